@@ -0,0 +1,83 @@
+//! Relay for online races: puts players asking for the same race into rooms,
+//! starts the race with a shared seed and passes everyone's progress along.
+//! Message types are shared with the game client.
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server;
+#[cfg(test)]
+mod tests;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::{App, State};
+
+/// Given by the relay to every connection
+pub type ClientId = u64;
+
+pub const DEFAULT_PORT: u16 = 1155;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ClientMessage {
+    /// Asks to race against players with the same `room`,
+    /// which the game sets to its serialized difficulty
+    Join {
+        name: String,
+        room: String,
+    },
+    /// Measures the round trip time, `time` is on the clock of the client and comes back in a pong
+    Ping {
+        time: f32,
+    },
+    /// Sent periodically during the run
+    Progress {
+        score: f32,
+        lives: usize,
+    },
+    Delivered {
+        score: f32,
+    },
+    Finished {
+        score: f32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ServerMessage {
+    /// First message after connecting
+    Welcome {
+        id: ClientId,
+    },
+    /// A player joined the room, the one who joined gets it too
+    /// and also gets one for everyone already there
+    Joined {
+        id: ClientId,
+        name: String,
+    },
+    Left {
+        id: ClientId,
+    },
+    /// Answer to [ClientMessage::Ping]
+    Pong {
+        time: f32,
+    },
+    /// Everyone in the room starts the run with this seed after `countdown` seconds.
+    /// Clients subtract the time it took to get here, half of their round trip time
+    Start {
+        seed: u64,
+        countdown: f32,
+    },
+    Progress {
+        id: ClientId,
+        score: f32,
+        lives: usize,
+    },
+    Delivered {
+        id: ClientId,
+        score: f32,
+    },
+    Finished {
+        id: ClientId,
+        score: f32,
+    },
+}
@@ -0,0 +1,25 @@
+use clap::Parser;
+
+use juggle_mail_relay::{App, State, DEFAULT_PORT};
+
+#[derive(Parser)]
+struct Args {
+    /// Address to listen on
+    #[clap(long, default_value = "127.0.0.1")]
+    host: String,
+    #[clap(long, default_value_t = DEFAULT_PORT)]
+    port: u16,
+    /// Race starts once this many players asked for the same one
+    #[clap(long, default_value_t = 2)]
+    players: usize,
+    /// Seconds between the race starting and the run beginning
+    #[clap(long, default_value_t = 3.0)]
+    countdown: f32,
+}
+
+fn main() {
+    let args = Args::parse();
+    let app = App::new(State::new(args.players, args.countdown));
+    eprintln!("Relay listening on ws://{}:{}", args.host, args.port);
+    geng_net::Server::new(app, (args.host.as_str(), args.port)).run();
+}
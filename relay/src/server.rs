@@ -0,0 +1,180 @@
+use super::*;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use geng_net::Sender;
+
+struct Room {
+    key: String,
+    racers: Vec<ClientId>,
+    started: bool,
+}
+
+/// Everything the relay knows, separate from [App] so that it can be used without sockets
+pub struct State {
+    players_per_race: usize,
+    countdown: f32,
+    next_id: ClientId,
+    senders: HashMap<ClientId, Box<dyn Sender<ServerMessage>>>,
+    names: HashMap<ClientId, String>,
+    rooms: Vec<Room>,
+}
+
+impl State {
+    /// A race starts once `players_per_race` players are in the room
+    pub fn new(players_per_race: usize, countdown: f32) -> Self {
+        Self {
+            players_per_race,
+            countdown,
+            next_id: 0,
+            senders: HashMap::new(),
+            names: HashMap::new(),
+            rooms: Vec::new(),
+        }
+    }
+
+    pub fn connect(&mut self, mut sender: Box<dyn Sender<ServerMessage>>) -> ClientId {
+        let id = self.next_id;
+        self.next_id += 1;
+        sender.send(ServerMessage::Welcome { id });
+        self.senders.insert(id, sender);
+        id
+    }
+
+    pub fn disconnect(&mut self, id: ClientId) {
+        self.senders.remove(&id);
+        self.names.remove(&id);
+        if let Some(index) = self.room_of(id) {
+            self.rooms[index].racers.retain(|&racer| racer != id);
+            self.broadcast(index, None, ServerMessage::Left { id });
+        }
+        self.rooms.retain(|room| !room.racers.is_empty());
+    }
+
+    pub fn handle(&mut self, id: ClientId, message: ClientMessage) {
+        match message {
+            ClientMessage::Join { name, room } => self.join(id, name, room),
+            ClientMessage::Ping { time } => self.send(id, ServerMessage::Pong { time }),
+            ClientMessage::Progress { score, lives } => {
+                self.forward(id, ServerMessage::Progress { id, score, lives });
+            }
+            ClientMessage::Delivered { score } => {
+                self.forward(id, ServerMessage::Delivered { id, score });
+            }
+            ClientMessage::Finished { score } => {
+                self.forward(id, ServerMessage::Finished { id, score });
+            }
+        }
+    }
+
+    fn join(&mut self, id: ClientId, name: String, key: String) {
+        if self.room_of(id).is_some() {
+            return;
+        }
+        // Started races are not joined, a new room waits for the next one
+        let index = match self
+            .rooms
+            .iter()
+            .position(|room| room.key == key && !room.started)
+        {
+            Some(index) => index,
+            None => {
+                self.rooms.push(Room {
+                    key,
+                    racers: Vec::new(),
+                    started: false,
+                });
+                self.rooms.len() - 1
+            }
+        };
+        let racers = self.rooms[index]
+            .racers
+            .iter()
+            .map(|racer| (*racer, self.names[racer].clone()))
+            .collect::<Vec<_>>();
+        for (racer, name) in racers {
+            self.send(id, ServerMessage::Joined { id: racer, name });
+        }
+        self.names.insert(id, name.clone());
+        self.rooms[index].racers.push(id);
+        self.broadcast(index, None, ServerMessage::Joined { id, name });
+        let room = &mut self.rooms[index];
+        if room.racers.len() >= self.players_per_race {
+            room.started = true;
+            let message = ServerMessage::Start {
+                seed: rand::random(),
+                countdown: self.countdown,
+            };
+            self.broadcast(index, None, message);
+        }
+    }
+
+    /// Sends to everyone else in the room of `from`
+    fn forward(&mut self, from: ClientId, message: ServerMessage) {
+        if let Some(index) = self.room_of(from) {
+            self.broadcast(index, Some(from), message);
+        }
+    }
+
+    fn broadcast(&mut self, room: usize, except: Option<ClientId>, message: ServerMessage) {
+        for racer in self.rooms[room].racers.clone() {
+            if Some(racer) != except {
+                self.send(racer, message.clone());
+            }
+        }
+    }
+
+    fn send(&mut self, id: ClientId, message: ServerMessage) {
+        if let Some(sender) = self.senders.get_mut(&id) {
+            sender.send(message);
+        }
+    }
+
+    fn room_of(&self, id: ClientId) -> Option<usize> {
+        self.rooms.iter().position(|room| room.racers.contains(&id))
+    }
+}
+
+/// Plugs [State] into [geng_net::Server]
+pub struct App {
+    state: Arc<Mutex<State>>,
+}
+
+impl App {
+    pub fn new(state: State) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+}
+
+pub struct Client {
+    id: ClientId,
+    state: Arc<Mutex<State>>,
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        self.state.lock().unwrap().disconnect(self.id);
+    }
+}
+
+impl geng_net::Receiver<ClientMessage> for Client {
+    fn handle(&mut self, message: ClientMessage) {
+        self.state.lock().unwrap().handle(self.id, message);
+    }
+}
+
+impl geng_net::App for App {
+    type Client = Client;
+    type ServerMessage = ServerMessage;
+    type ClientMessage = ClientMessage;
+    fn connect(&mut self, sender: Box<dyn Sender<ServerMessage>>) -> Client {
+        let id = self.state.lock().unwrap().connect(sender);
+        Client {
+            id,
+            state: self.state.clone(),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use super::*;
+
+use std::sync::{Arc, Mutex};
+
+/// Collects what the relay sends to one client
+#[derive(Clone, Default)]
+struct Inbox(Arc<Mutex<Vec<ServerMessage>>>);
+
+impl geng_net::Sender<ServerMessage> for Inbox {
+    fn send(&mut self, message: ServerMessage) {
+        self.0.lock().unwrap().push(message);
+    }
+}
+
+impl Inbox {
+    fn take(&self) -> Vec<ServerMessage> {
+        std::mem::take(&mut self.0.lock().unwrap())
+    }
+}
+
+fn connect(state: &mut State) -> (ClientId, Inbox) {
+    let inbox = Inbox::default();
+    let id = state.connect(Box::new(inbox.clone()));
+    assert_eq!(inbox.take(), [ServerMessage::Welcome { id }]);
+    (id, inbox)
+}
+
+fn join(state: &mut State, id: ClientId, name: &str, room: &str) {
+    state.handle(
+        id,
+        ClientMessage::Join {
+            name: name.to_owned(),
+            room: room.to_owned(),
+        },
+    );
+}
+
+fn start_seed(messages: &[ServerMessage]) -> Option<u64> {
+    messages.iter().find_map(|message| match message {
+        ServerMessage::Start { seed, .. } => Some(*seed),
+        _ => None,
+    })
+}
+
+#[test]
+fn race_starts_with_the_same_seed_for_everyone() {
+    let mut state = State::new(2, 3.0);
+    let (a, a_inbox) = connect(&mut state);
+    let (b, b_inbox) = connect(&mut state);
+
+    join(&mut state, a, "a", "easy");
+    assert_eq!(
+        a_inbox.take(),
+        [ServerMessage::Joined {
+            id: a,
+            name: "a".to_owned(),
+        }]
+    );
+
+    join(&mut state, b, "b", "easy");
+    let a_messages = a_inbox.take();
+    let b_messages = b_inbox.take();
+    assert!(b_messages.contains(&ServerMessage::Joined {
+        id: a,
+        name: "a".to_owned(),
+    }));
+    assert!(a_messages.contains(&ServerMessage::Joined {
+        id: b,
+        name: "b".to_owned(),
+    }));
+    let seed = start_seed(&a_messages).expect("race should start");
+    assert_eq!(start_seed(&b_messages), Some(seed));
+}
+
+#[test]
+fn only_players_of_the_same_room_race_together() {
+    let mut state = State::new(2, 3.0);
+    let (a, a_inbox) = connect(&mut state);
+    let (b, b_inbox) = connect(&mut state);
+    join(&mut state, a, "a", "easy");
+    join(&mut state, b, "b", "hard");
+    assert_eq!(start_seed(&a_inbox.take()), None);
+    assert_eq!(start_seed(&b_inbox.take()), None);
+
+    // Started races are not joined
+    let (c, c_inbox) = connect(&mut state);
+    join(&mut state, c, "c", "easy");
+    assert!(start_seed(&c_inbox.take()).is_some());
+    let (d, d_inbox) = connect(&mut state);
+    join(&mut state, d, "d", "easy");
+    assert_eq!(d_inbox.take().len(), 1);
+}
+
+#[test]
+fn pings_are_answered_to_the_sender_only() {
+    let mut state = State::new(2, 3.0);
+    let (a, a_inbox) = connect(&mut state);
+    let (b, b_inbox) = connect(&mut state);
+    join(&mut state, a, "a", "easy");
+    join(&mut state, b, "b", "easy");
+    a_inbox.take();
+    b_inbox.take();
+
+    state.handle(a, ClientMessage::Ping { time: 1.5 });
+    assert_eq!(a_inbox.take(), [ServerMessage::Pong { time: 1.5 }]);
+    assert!(b_inbox.take().is_empty());
+}
+
+#[test]
+fn progress_is_forwarded_to_rivals_only() {
+    let mut state = State::new(2, 3.0);
+    let (a, a_inbox) = connect(&mut state);
+    let (b, b_inbox) = connect(&mut state);
+    join(&mut state, a, "a", "easy");
+    join(&mut state, b, "b", "easy");
+    a_inbox.take();
+    b_inbox.take();
+
+    state.handle(
+        a,
+        ClientMessage::Progress {
+            score: 100.0,
+            lives: 2,
+        },
+    );
+    state.handle(a, ClientMessage::Delivered { score: 50.0 });
+    assert!(a_inbox.take().is_empty());
+    assert_eq!(
+        b_inbox.take(),
+        [
+            ServerMessage::Progress {
+                id: a,
+                score: 100.0,
+                lives: 2,
+            },
+            ServerMessage::Delivered { id: a, score: 50.0 },
+        ]
+    );
+
+    state.disconnect(a);
+    assert_eq!(b_inbox.take(), [ServerMessage::Left { id: a }]);
+}
+
+#[test]
+fn rest_of_the_race_goes_on_when_a_client_disconnects() {
+    let mut state = State::new(3, 3.0);
+    let (a, a_inbox) = connect(&mut state);
+    let (b, b_inbox) = connect(&mut state);
+    let (c, c_inbox) = connect(&mut state);
+    join(&mut state, a, "a", "easy");
+    join(&mut state, b, "b", "easy");
+    join(&mut state, c, "c", "easy");
+    assert!(start_seed(&a_inbox.take()).is_some());
+    b_inbox.take();
+    c_inbox.take();
+
+    state.disconnect(a);
+    assert_eq!(b_inbox.take(), [ServerMessage::Left { id: a }]);
+    assert_eq!(c_inbox.take(), [ServerMessage::Left { id: a }]);
+
+    // Whatever was still in flight from the disconnected client goes nowhere
+    state.handle(a, ClientMessage::Finished { score: 100.0 });
+    assert!(b_inbox.take().is_empty());
+    assert!(c_inbox.take().is_empty());
+
+    state.handle(b, ClientMessage::Finished { score: 200.0 });
+    assert!(a_inbox.take().is_empty());
+    assert_eq!(
+        c_inbox.take(),
+        [ServerMessage::Finished {
+            id: b,
+            score: 200.0
+        }]
+    );
+
+    // The race stays started, newcomers wait for a race of their own
+    let (d, d_inbox) = connect(&mut state);
+    join(&mut state, d, "d", "easy");
+    assert_eq!(
+        d_inbox.take(),
+        [ServerMessage::Joined {
+            id: d,
+            name: "d".to_owned(),
+        }]
+    );
+    assert!(c_inbox.take().is_empty());
+}
@@ -0,0 +1,132 @@
+//! Relay and two clients talking over real websockets on localhost
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use juggle_mail_relay::{App, ClientId, ClientMessage, ServerMessage, State};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Client {
+    connection: geng_net::client::Connection<ServerMessage, ClientMessage>,
+    inbox: VecDeque<ServerMessage>,
+}
+
+impl Client {
+    /// Retries until the relay started listening
+    fn connect(address: &str) -> Self {
+        let start = Instant::now();
+        loop {
+            match futures::executor::block_on(geng_net::client::connect(address)) {
+                Ok(connection) => {
+                    return Self {
+                        connection,
+                        inbox: VecDeque::new(),
+                    }
+                }
+                Err(e) => assert!(start.elapsed() < TIMEOUT, "Failed to connect: {e:#}"),
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn send(&mut self, message: ClientMessage) {
+        self.connection.send(message);
+    }
+
+    fn receive(&mut self) -> ServerMessage {
+        let start = Instant::now();
+        loop {
+            for message in self.connection.new_messages() {
+                self.inbox.push_back(message.unwrap());
+            }
+            if let Some(message) = self.inbox.pop_front() {
+                return message;
+            }
+            assert!(start.elapsed() < TIMEOUT, "Relay did not answer");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn welcome(&mut self) -> ClientId {
+        match self.receive() {
+            ServerMessage::Welcome { id } => id,
+            message => panic!("Expected a welcome, got {message:?}"),
+        }
+    }
+
+    fn join(&mut self, name: &str) {
+        self.send(ClientMessage::Join {
+            name: name.to_owned(),
+            room: "easy".to_owned(),
+        });
+    }
+}
+
+fn start_seed(message: ServerMessage) -> u64 {
+    match message {
+        ServerMessage::Start { seed, .. } => seed,
+        message => panic!("Expected the race to start, got {message:?}"),
+    }
+}
+
+#[test]
+fn two_clients_race_through_the_relay() {
+    // Port 0 lets the system pick a free one
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let server = geng_net::Server::new(App::new(State::new(2, 3.0)), ("127.0.0.1", port));
+    let server_handle = server.handle();
+    std::thread::spawn(move || server.run());
+    let address = format!("ws://127.0.0.1:{port}");
+
+    let mut a = Client::connect(&address);
+    let a_id = a.welcome();
+    let mut b = Client::connect(&address);
+    let b_id = b.welcome();
+
+    a.join("a");
+    let joined = |id: ClientId, name: &str| ServerMessage::Joined {
+        id,
+        name: name.to_owned(),
+    };
+    assert_eq!(a.receive(), joined(a_id, "a"));
+    b.join("b");
+    assert_eq!(b.receive(), joined(a_id, "a"));
+    assert_eq!(b.receive(), joined(b_id, "b"));
+    assert_eq!(a.receive(), joined(b_id, "b"));
+    let seed = start_seed(a.receive());
+    assert_eq!(start_seed(b.receive()), seed);
+
+    a.send(ClientMessage::Ping { time: 1.5 });
+    assert_eq!(a.receive(), ServerMessage::Pong { time: 1.5 });
+
+    a.send(ClientMessage::Progress {
+        score: 100.0,
+        lives: 2,
+    });
+    a.send(ClientMessage::Delivered { score: 50.0 });
+    assert_eq!(
+        b.receive(),
+        ServerMessage::Progress {
+            id: a_id,
+            score: 100.0,
+            lives: 2,
+        }
+    );
+    assert_eq!(
+        b.receive(),
+        ServerMessage::Delivered {
+            id: a_id,
+            score: 50.0,
+        }
+    );
+
+    drop(a);
+    assert_eq!(b.receive(), ServerMessage::Left { id: a_id });
+
+    server_handle.shutdown();
+}
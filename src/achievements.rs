@@ -0,0 +1,189 @@
+//! Goals from config that get unlocked over all runs, progress is kept on this device
+
+use super::*;
+
+use crate::simulation::{Event, Simulation};
+
+const KEY: &str = "achievements";
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Progress {
+    pub deliveries: usize,
+    pub drops: usize,
+    /// Most items in the air at once
+    pub juggling: usize,
+    pub best_score: f32,
+    pub flawless_runs: usize,
+    /// Names of the skins played with
+    pub skins: HashSet<String>,
+    /// Names of unlocked achievements
+    pub unlocked: HashSet<String>,
+}
+
+impl Progress {
+    pub fn load() -> Self {
+        preferences::load(KEY).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        preferences::save(KEY, self);
+    }
+
+    /// Updates the counters with what happened during a simulation step
+    pub fn track(&mut self, simulation: &Simulation, events: &[Event]) {
+        for event in events {
+            match event {
+                Event::Delivered { .. } => self.deliveries += 1,
+                Event::Dropped { .. } => self.drops += 1,
+                Event::Juggled => {
+                    self.juggling = self.juggling.max(simulation.juggling_items.len());
+                }
+                // Practice runs can not lose lives
+                Event::GameOver { .. } if !simulation.diff.practice => {
+                    let lives = simulation.diff.lives;
+                    if simulation.lives == lives
+                        && simulation
+                            .players
+                            .iter()
+                            .all(|player| player.lives == lives)
+                    {
+                        self.flawless_runs += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        // Practice runs go on for as long as the player wants
+        if !simulation.diff.practice {
+            self.best_score = self.best_score.max(simulation.score);
+        }
+    }
+
+    /// Progress towards the goal and the target to reach
+    pub fn goal_progress(&self, goal: AchievementGoal) -> (f32, f32) {
+        match goal {
+            AchievementGoal::Deliveries(target) => (self.deliveries as f32, target as f32),
+            AchievementGoal::Drops(target) => (self.drops as f32, target as f32),
+            AchievementGoal::Juggling(target) => (self.juggling as f32, target as f32),
+            AchievementGoal::Score(target) => (self.best_score, target),
+            AchievementGoal::FlawlessRuns(target) => (self.flawless_runs as f32, target as f32),
+            AchievementGoal::Skins(target) => (self.skins.len() as f32, target as f32),
+        }
+    }
+
+    /// Marks the achievements with reached goals as unlocked, returns the newly unlocked ones
+    pub fn unlock<'a>(&mut self, achievements: &'a [Achievement]) -> Vec<&'a Achievement> {
+        let mut unlocked = Vec::new();
+        for achievement in achievements {
+            let (progress, target) = self.goal_progress(achievement.goal);
+            if progress >= target && self.unlocked.insert(achievement.name.clone()) {
+                unlocked.push(achievement);
+            }
+        }
+        unlocked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::simulation::{GameOverReason, TouchInput, TouchKind};
+    use crate::testing::*;
+
+    fn touch(simulation: &mut Simulation, kind: TouchKind, position: vec2<f32>) -> Vec<Event> {
+        simulation.input(TouchInput {
+            kind,
+            id: None,
+            position,
+            player: 0,
+        });
+        simulation.take_events()
+    }
+
+    #[test]
+    fn achievements_unlock_from_simulation_events() {
+        let mut simulation = simulation(diff());
+        let mut progress = Progress::default();
+        let achievements = vec![
+            Achievement {
+                name: "juggler".to_owned(),
+                goal: AchievementGoal::Juggling(2),
+            },
+            Achievement {
+                name: "clumsy".to_owned(),
+                goal: AchievementGoal::Drops(2),
+            },
+        ];
+        let step = |simulation: &mut Simulation, events: Vec<Event>, progress: &mut Progress| {
+            progress.track(simulation, &events);
+            progress
+                .unlock(&achievements)
+                .iter()
+                .map(|achievement| achievement.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let bag = simulation.players[0].bag_position.center();
+        let mut events = touch(&mut simulation, TouchKind::Start, bag);
+        events.extend(touch(&mut simulation, TouchKind::End, bag));
+        assert!(step(&mut simulation, events, &mut progress).is_empty());
+        let mut events = touch(&mut simulation, TouchKind::Start, bag);
+        events.extend(touch(&mut simulation, TouchKind::End, bag));
+        assert_eq!(step(&mut simulation, events, &mut progress), ["juggler"]);
+        assert_eq!(progress.juggling, 2);
+
+        let delta_time = 1.0 / simulation.config.simulation_fps;
+        let mut events = Vec::new();
+        for _ in 0..(5.0 / delta_time).ceil() as usize {
+            simulation.update(delta_time);
+            events.extend(simulation.take_events());
+        }
+        assert_eq!(step(&mut simulation, events, &mut progress), ["clumsy"]);
+        assert_eq!(progress.drops, 2);
+        // Only unlocks once
+        assert!(step(&mut simulation, vec![], &mut progress).is_empty());
+    }
+
+    #[test]
+    fn practice_runs_do_not_count_for_score_and_flawless_runs() {
+        let game_over = [Event::GameOver {
+            reason: GameOverReason::TimeUp,
+        }];
+        let mut progress = Progress::default();
+
+        let mut practice = simulation(Difficulty {
+            practice: true,
+            ..diff()
+        });
+        practice.score = 10000.0;
+        progress.track(&practice, &game_over);
+        assert_eq!(progress.best_score, 0.0);
+        assert_eq!(progress.flawless_runs, 0);
+
+        let mut scored = simulation(diff());
+        scored.score = 5000.0;
+        progress.track(&scored, &game_over);
+        assert_eq!(progress.best_score, 5000.0);
+        assert_eq!(progress.flawless_runs, 1);
+    }
+
+    #[test]
+    fn restarts_keep_the_counters_of_the_previous_attempt() {
+        let first = RunMode::Played(Rc::new(RefCell::new(Progress::default())));
+        // The next attempt is started before the previous one is dropped and saves the progress
+        let restarted = first.clone();
+        let simulation = simulation(diff());
+        let dropped = [Event::Dropped { pos: vec2::ZERO }];
+        for mode in [&first, &restarted] {
+            if let RunMode::Played(progress) = mode {
+                progress.borrow_mut().track(&simulation, &dropped);
+            }
+        }
+        let RunMode::Played(progress) = restarted else {
+            unreachable!()
+        };
+        assert_eq!(progress.borrow().drops, 2);
+    }
+}
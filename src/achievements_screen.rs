@@ -0,0 +1,113 @@
+use super::*;
+
+/// How many rows fit on the screen
+const ROWS: usize = 8;
+
+pub struct AchievementsScreen {
+    geng: Geng,
+    assets: Rc<Assets>,
+    config: Rc<Config>,
+    progress: achievements::Progress,
+    page: usize,
+
+    transition: Option<geng::state::Transition>,
+}
+
+impl AchievementsScreen {
+    pub fn new(geng: &Geng, assets: &Rc<Assets>, config: &Rc<Config>) -> Self {
+        Self {
+            geng: geng.clone(),
+            assets: assets.clone(),
+            config: config.clone(),
+            progress: achievements::Progress::load(),
+            page: 0,
+            transition: None,
+        }
+    }
+
+    fn pages(&self) -> usize {
+        ((self.config.achievements.len() + ROWS - 1) / ROWS).max(1)
+    }
+}
+
+impl geng::State for AchievementsScreen {
+    fn transition(&mut self) -> Option<geng::state::Transition> {
+        self.transition.take()
+    }
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        self.geng
+            .window()
+            .set_cursor_type(geng::CursorType::Default);
+        ugli::clear(framebuffer, Some(Rgba::BLACK), None, None);
+    }
+
+    fn ui<'a>(&'a mut self, cx: &'a geng::ui::Controller) -> Box<dyn geng::ui::Widget + 'a> {
+        use geng::ui::*;
+        let back = ui::TextureButton::new(cx, &self.assets.back, &self.assets.ui_sfx);
+        if back.was_clicked() {
+            self.transition = Some(geng::state::Transition::Pop);
+        }
+        let prev = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            "prev".to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if prev.was_clicked() {
+            self.page = self.page.saturating_sub(1);
+        }
+        let next = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            "next".to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if next.was_clicked() {
+            self.page = (self.page + 1).min(self.pages() - 1);
+        }
+        let unlocked = self
+            .config
+            .achievements
+            .iter()
+            .filter(|achievement| self.progress.unlocked.contains(&achievement.name))
+            .count();
+        let title = ui::Text::new(
+            &self.assets.font,
+            format!("achievements {unlocked}:{}", self.config.achievements.len()),
+        );
+        let page = ui::Text::new(
+            &self.assets.font,
+            format!("{}:{}", self.page + 1, self.pages()),
+        );
+
+        let mut stack = stack![
+            ui::TextureWidget::new(&self.assets.screen_background),
+            title.fixed_size(vec2(0.0, 16.0)).place(200, 85),
+            back.place(25, 235),
+            prev.fixed_size(vec2(48.0, 12.0)).place(150, 250),
+            page.fixed_size(vec2(0.0, 12.0)).place(262, 250),
+            next.fixed_size(vec2(48.0, 12.0)).place(327, 250),
+        ];
+        let mut y = 110;
+        for achievement in self
+            .config
+            .achievements
+            .iter()
+            .skip(self.page * ROWS)
+            .take(ROWS)
+        {
+            let progress = if self.progress.unlocked.contains(&achievement.name) {
+                "done".to_owned()
+            } else {
+                let (progress, target) = self.progress.goal_progress(achievement.goal);
+                format!("{}:{}", progress.floor() as i64, target as i64)
+            };
+            let name = ui::Text::left_align(&self.assets.font, achievement.name.clone());
+            stack.push(Box::new(name.fixed_size(vec2(0.0, 12.0)).place(25, y)));
+            let progress = ui::Text::right_align(&self.assets.font, progress);
+            stack.push(Box::new(progress.fixed_size(vec2(0.0, 12.0)).place(375, y)));
+            y += 13;
+        }
+        stack.center().boxed()
+    }
+}
@@ -70,32 +70,219 @@ pub struct DifficultyAssets {
     pub time_scale: Vec<Texture>,
     #[load(listed_in = "_list.ron")]
     pub lives: Vec<Texture>,
+    pub endless: Texture,
+}
+
+impl DifficultyAssets {
+    /// Index one past [Config::game_time] is endless mode
+    pub fn game_time_texture(&self, index: usize) -> &Texture {
+        self.game_time.get(index).unwrap_or(&self.endless)
+    }
+}
+
+#[derive(geng::asset::Load)]
+pub struct PowerUpAssets {
+    pub slow_motion: Texture,
+    pub extra_life: Texture,
+    pub double_multiplier: Texture,
+    pub magnet: Texture,
+}
+
+impl PowerUpAssets {
+    pub fn get(&self, kind: PowerUpKind) -> &Texture {
+        match kind {
+            PowerUpKind::SlowMotion => &self.slow_motion,
+            PowerUpKind::ExtraLife => &self.extra_life,
+            PowerUpKind::DoubleMultiplier => &self.double_multiplier,
+            PowerUpKind::Magnet => &self.magnet,
+        }
+    }
 }
 
 #[derive(geng::asset::Load)]
 pub struct SkinAssets {
-    pub hand: Texture,
-    pub holding_hand: Texture,
-    pub bike: Texture,
+    pub hand: Rc<Texture>,
+    pub holding_hand: Rc<Texture>,
+    pub bike: Rc<Texture>,
     pub newspaper: Rc<Texture>,
-    pub bag: Texture,
+    pub bag: Rc<Texture>,
+}
+
+#[derive(Deref)]
+pub struct Skin {
+    #[deref]
+    pub textures: SkinAssets,
+    pub display_name: String,
+    /// Multiplies the color of every skin texture
+    pub tint: Rgba<f32>,
+    /// Size of the hand and the bike, does not affect gameplay
+    pub scale: f32,
+}
+
+impl Skin {
+    pub fn tinted(&self, color: Rgba<f32>) -> Rgba<f32> {
+        Rgba::new(
+            color.r * self.tint.r,
+            color.g * self.tint.g,
+            color.b * self.tint.b,
+            color.a * self.tint.a,
+        )
+    }
+}
+
+/// `pack.ron` in the root of a skin pack
+#[derive(Deserialize)]
+struct PackManifest {
+    name: String,
+    author: String,
+    skins: Vec<SkinManifest>,
+}
+
+#[derive(Deserialize)]
+struct SkinManifest {
+    /// Directory with the textures inside of the pack
+    dir: String,
+    /// Directory name if not specified
+    display_name: Option<String>,
+    tint: Option<Rgba<f32>>,
+    scale: Option<f32>,
+}
+
+pub struct Skins {
+    names: Vec<String>,
+    skins: HashMap<String, Skin>,
+}
+
+impl Skins {
+    /// Bundled skins in the order of the list file with default first, then the skin packs
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Unknown skins fall back to the default one
+    pub fn get(&self, name: &str) -> &Skin {
+        self.skins.get(name).unwrap_or(&self.skins["default"])
+    }
+
+    /// Adds skins from every pack in the directory. A broken pack is skipped
+    pub async fn load_packs(&mut self, manager: &geng::asset::Manager, dir: &std::path::Path) {
+        let pack_dirs = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| path.is_dir())
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                log::info!("No skin packs loaded from {dir:?}: {e}");
+                return;
+            }
+        };
+        for path in pack_dirs {
+            match self.load_pack(manager, &path).await {
+                Ok(skins) => {
+                    log::info!("Loaded skin pack from {path:?}");
+                    for (name, skin) in skins {
+                        self.names.push(name.clone());
+                        self.skins.insert(name, skin);
+                    }
+                }
+                Err(e) => log::error!("Skipping skin pack {path:?}: {e:#}"),
+            }
+        }
+    }
+
+    async fn load_pack(
+        &self,
+        manager: &geng::asset::Manager,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Vec<(String, Skin)>> {
+        let manifest: PackManifest = file::load_detect(path.join("pack.ron")).await?;
+        let pack = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow::anyhow!("pack directory name is not valid utf-8"))?;
+        let default = &self.skins["default"].textures;
+        let mut skins = Vec::new();
+        for skin in manifest.skins {
+            let dir = path.join(&skin.dir);
+            // Missing textures are taken from the default skin
+            let load = |file_name: &str, default: &Rc<Texture>| {
+                let path = dir.join(file_name);
+                let default = default.clone();
+                async move {
+                    if !path.exists() {
+                        return Ok::<_, anyhow::Error>(default);
+                    }
+                    let texture: Texture = manager.load(&path).await?;
+                    Ok(Rc::new(texture))
+                }
+            };
+            let textures = SkinAssets {
+                hand: load("hand.png", &default.hand).await?,
+                holding_hand: load("holding_hand.png", &default.holding_hand).await?,
+                bike: load("bike.png", &default.bike).await?,
+                newspaper: load("newspaper.png", &default.newspaper).await?,
+                bag: load("bag.png", &default.bag).await?,
+            };
+            skins.push((
+                format!("{pack}:{}", skin.dir),
+                Skin {
+                    textures,
+                    display_name: skin.display_name.unwrap_or(skin.dir),
+                    tint: skin.tint.unwrap_or(Rgba::WHITE),
+                    scale: skin.scale.unwrap_or(1.0),
+                },
+            ));
+        }
+        log::info!("Skin pack {:?} by {}", manifest.name, manifest.author);
+        Ok(skins)
+    }
+}
+
+/// Per-user directory to load skin packs from
+pub fn skin_packs_dir() -> Option<std::path::PathBuf> {
+    #[cfg(target_arch = "wasm32")]
+    return None;
+    #[cfg(not(target_arch = "wasm32"))]
+    directories::ProjectDirs::from("", "", "juggle-mail")
+        .map(|dirs| dirs.data_dir().join("skin-packs"))
 }
 
 async fn load_skins(
     manager: &geng::asset::Manager,
     path: impl AsRef<std::path::Path>,
-) -> anyhow::Result<HashMap<String, SkinAssets>> {
+) -> anyhow::Result<Skins> {
     let path = path.as_ref();
-    let list: Vec<String> = file::load_detect(path.join("_list.ron")).await?;
-    Ok(
-        future::try_join_all(list.into_iter().map(|name| async move {
-            let skin: SkinAssets = manager.load(path.join(&name)).await?;
-            Ok::<_, anyhow::Error>((name, skin))
-        }))
-        .await?
-        .into_iter()
-        .collect(),
-    )
+    let names: Vec<String> = file::load_detect(path.join("_list.ron")).await?;
+    let skins = future::try_join_all(names.iter().map(|name| async move {
+        let textures: SkinAssets = manager.load(path.join(name)).await?;
+        let skin = Skin {
+            textures,
+            display_name: name.clone(),
+            tint: Rgba::WHITE,
+            scale: 1.0,
+        };
+        Ok::<_, anyhow::Error>((name.clone(), skin))
+    }))
+    .await?
+    .into_iter()
+    .collect();
+    Ok(Skins { names, skins })
+}
+
+/// Textures by name from a directory with a `_list.ron`,
+/// used for things defined in config like [ItemKind::texture]
+async fn load_named_textures(
+    manager: &geng::asset::Manager,
+    path: impl AsRef<std::path::Path>,
+) -> anyhow::Result<HashMap<String, Texture>> {
+    let path = path.as_ref();
+    let names: Vec<String> = file::load_detect(path.join("_list.ron")).await?;
+    let textures = future::try_join_all(names.into_iter().map(|name| async move {
+        let texture: Texture = manager.load(path.join(format!("{name}.png"))).await?;
+        Ok::<_, anyhow::Error>((name, texture))
+    }))
+    .await?;
+    Ok(textures.into_iter().collect())
 }
 
 #[derive(geng::asset::Load)]
@@ -106,8 +293,13 @@ pub struct Assets {
     pub sfx: Sfx,
     pub envelope_highlight: Texture,
     #[load(load_with = "load_skins(&manager, base_path.join(\"skins\"))")]
-    pub skins: HashMap<String, SkinAssets>,
+    pub skins: Skins,
+    #[load(load_with = "load_named_textures(&manager, base_path.join(\"items\"))")]
+    pub items: HashMap<String, Texture>,
+    #[load(load_with = "load_named_textures(&manager, base_path.join(\"hazards\"))")]
+    pub hazards: HashMap<String, Texture>,
     pub mailbox: Texture,
+    pub power_ups: PowerUpAssets,
     pub aim: Texture,
     #[load(postprocess = "road_postprocess")]
     pub road: Texture,
@@ -124,15 +316,24 @@ pub struct Assets {
     pub timer_arrow: Texture,
     pub font: Font,
     pub score_background: Texture,
-    #[cfg_attr(not(feature = "leaderboard"), load(path = "main_menu_no_lb.png"))]
     pub main_menu: Texture,
-    #[cfg_attr(not(feature = "leaderboard"), load(path = "final_screen_no_lb.png"))]
     pub final_screen: Texture,
     pub play_button: Texture,
     pub play_again: Texture,
     pub menu: Texture,
     pub leaderboard_button: Texture,
-    pub leaderboard_background: Texture,
+    /// Title artwork alone, for screens without artwork of their own
+    pub screen_background: Texture,
+}
+
+impl Assets {
+    /// Texture of an item kind, unknown textures fall back to the skin newspaper
+    pub fn item_texture<'a>(&'a self, kind: &ItemKind, skin: &'a Skin) -> &'a Texture {
+        kind.texture
+            .as_ref()
+            .and_then(|name| self.items.get(name))
+            .unwrap_or(&skin.newspaper)
+    }
 }
 
 fn road_postprocess(texture: &mut Texture) {
@@ -0,0 +1,224 @@
+//! Plays the game through the same inputs as a player would,
+//! for the attract mode demo and for estimating scores without a window
+
+use super::*;
+
+use crate::simulation::{AssetInfo, Item, Simulation, TouchInput, TouchKind};
+
+/// Drives a single mouse-like touch of the first player
+pub struct Bot {
+    config: BotConfig,
+    rng: StdRng,
+    /// Time until the bot can act again
+    cooldown: f32,
+}
+
+impl Bot {
+    pub fn new(config: &BotConfig, seed: u64) -> Self {
+        Self {
+            config: config.clone(),
+            rng: StdRng::seed_from_u64(seed),
+            cooldown: 0.0,
+        }
+    }
+
+    /// Inputs to send before the next simulation step
+    pub fn step(&mut self, simulation: &Simulation, delta_time: f32) -> Vec<TouchInput> {
+        self.cooldown -= delta_time;
+        if self.cooldown > 0.0 || simulation.game_over {
+            return Vec::new();
+        }
+        let holding = simulation
+            .touches
+            .iter()
+            .find(|touch| touch.id.is_none())
+            .and_then(|touch| touch.holding.as_ref());
+        let inputs = match holding {
+            Some(item) => self.throw(simulation, item),
+            None => self.pick(simulation),
+        };
+        if !inputs.is_empty() {
+            self.cooldown = self.config.reaction_delay;
+        }
+        inputs
+    }
+
+    /// Catches the item that is about to drop, or takes a new one from the bag
+    fn pick(&mut self, simulation: &Simulation) -> Vec<TouchInput> {
+        let target = match self.urgent(simulation) {
+            Some(item) => item.pos,
+            None if simulation.juggling_items.len() < self.config.juggle_items => {
+                simulation.players[0].bag_position.center()
+            }
+            None => return Vec::new(),
+        };
+        let target = target + self.aim_error();
+        vec![
+            input(TouchKind::Move, target),
+            input(TouchKind::Start, target),
+        ]
+    }
+
+    /// Throws the held item at a mailbox that accepts it,
+    /// or tosses it back up if something else needs catching first
+    fn throw(&mut self, simulation: &Simulation, item: &Item) -> Vec<TouchInput> {
+        let aim = simulation
+            .mailboxes
+            .iter()
+            .enumerate()
+            .filter(|(_index, mailbox)| {
+                simulation.config.accepts(item.kind, mailbox.color)
+                    && item.mailbox_color.unwrap_or(mailbox.color) == mailbox.color
+            })
+            .find_map(|(index, mailbox)| {
+                let aim = simulation.mailbox_aim(mailbox)?;
+                (simulation.hovered_mailbox(aim) == Some(index)).then_some(aim)
+            });
+        let target = match aim {
+            Some(aim) => aim + self.aim_error(),
+            None if self.urgent(simulation).is_some() => {
+                simulation.players[0].bag_position.center() + vec2(0.0, 2.0)
+            }
+            None => return Vec::new(),
+        };
+        vec![
+            input(TouchKind::Move, target),
+            input(TouchKind::End, target),
+        ]
+    }
+
+    /// Juggled item that drops into the bag sooner than the bot could react twice
+    fn urgent<'a>(&self, simulation: &'a Simulation) -> Option<&'a Item> {
+        let bag = simulation.players[0].bag_position;
+        simulation
+            .juggling_items
+            .iter()
+            .map(|item| {
+                let gravity =
+                    simulation.gravity() * simulation.config.items[item.kind].gravity_scale;
+                // Solving pos.y + vel.y * t - gravity * t^2 / 2 = bag.min.y
+                let height = (item.pos.y - bag.min.y).max(0.0);
+                let time =
+                    (item.vel.y + (item.vel.y.sqr() + 2.0 * gravity * height).sqrt()) / gravity;
+                (item, time)
+            })
+            .filter(|(_item, time)| *time < self.config.reaction_delay * 2.0)
+            .min_by_key(|(_item, time)| r32(*time))
+            .map(|(item, _time)| item)
+    }
+
+    fn aim_error(&mut self) -> vec2<f32> {
+        vec2(
+            self.rng.gen_range(-1.0..=1.0),
+            self.rng.gen_range(-1.0..=1.0),
+        ) * self.config.aim_error
+    }
+}
+
+fn input(kind: TouchKind, position: vec2<f32>) -> TouchInput {
+    TouchInput {
+        kind,
+        id: None,
+        position,
+        player: 0,
+    }
+}
+
+/// Textures are not loaded without a window, so items are assumed to be square
+pub fn headless_asset_info(config: &Config) -> AssetInfo {
+    AssetInfo {
+        item_aspects: vec![1.0; config.items.len()],
+        house_textures: 1,
+    }
+}
+
+/// Score of a single run played by the bot, cut short after [BotConfig::estimate_time_limit]
+pub fn play(config: &Rc<Config>, diff: Difficulty, seed: u64) -> f32 {
+    let mut simulation = Simulation::new(config, diff, seed, headless_asset_info(config));
+    let mut bot = Bot::new(&config.bot, seed);
+    let delta_time = 1.0 / config.simulation_fps;
+    while !simulation.game_over && simulation.time < config.bot.estimate_time_limit {
+        for input in bot.step(&simulation, delta_time) {
+            simulation.input(input);
+        }
+        simulation.update(delta_time);
+        simulation.take_events();
+    }
+    simulation.score
+}
+
+/// Average score of the bot over `runs` seeds
+pub fn estimate_score(config: &Rc<Config>, diff: &Difficulty, runs: usize) -> f32 {
+    let total: f32 = (0..runs as u64)
+        .map(|seed| play(config, diff.clone(), seed))
+        .sum();
+    total / runs.max(1) as f32
+}
+
+/// Prints the expected score of every difficulty that can be picked in the main menu
+pub fn print_estimates(config: &Rc<Config>, runs: usize) {
+    for time_scale in 0..config.time_scale.len() {
+        // One past the end is endless mode
+        for game_time in 0..=config.game_time.len() {
+            for lives in 0..config.lives.len() {
+                for color_matching in [false, true] {
+                    let diff = Difficulty {
+                        color_matching,
+                        ..Difficulty::new(config, time_scale, game_time, lives)
+                    };
+                    let score = estimate_score(config, &diff, runs);
+                    println!(
+                        "time_scale {} game_time {} lives {} colors {color_matching}: {}",
+                        diff.time_scale,
+                        if diff.endless {
+                            "endless".to_owned()
+                        } else {
+                            diff.game_time.to_string()
+                        },
+                        diff.lives,
+                        score.floor() as i32,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::simulation::Event;
+    use crate::testing::*;
+
+    #[test]
+    fn bot_juggles_and_delivers() {
+        let config = config();
+        let mut simulation = Simulation::new(&config, diff(), 42, headless_asset_info(&config));
+        let mut bot = Bot::new(&config.bot, 42);
+        let delta_time = 1.0 / config.simulation_fps;
+        let mut events = Vec::new();
+        while !simulation.game_over {
+            for input in bot.step(&simulation, delta_time) {
+                simulation.input(input);
+            }
+            simulation.update(delta_time);
+            events.extend(simulation.take_events());
+        }
+        let count = |f: fn(&Event) -> bool| events.iter().filter(|event| f(event)).count();
+        assert!(count(|event| matches!(event, Event::Juggled)) > 0);
+        assert!(count(|event| matches!(event, Event::Delivered { .. })) > 0);
+    }
+
+    #[test]
+    fn bot_score_estimates_are_deterministic() {
+        let config = config();
+        let diff = Difficulty {
+            game_time: 30.0,
+            ..diff()
+        };
+        let estimate = estimate_score(&config, &diff, 2);
+        assert!(estimate > 0.0);
+        assert_eq!(estimate, estimate_score(&config, &diff, 2));
+    }
+}
@@ -1,6 +1,6 @@
 use super::*;
 
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 pub struct Config {
     pub sky_color: [Rgba<f32>; 2],
     pub gravity: f32,
@@ -9,7 +9,9 @@ pub struct Config {
     pub item_scale: f32,
     pub item_hold_scale: f32,
     pub hand_radius: f32,
-    pub item_max_w: f32,
+    pub rival_knock_speed: f32,
+    pub ghost_alpha: f32,
+    pub keyboard_cursor_speed: f32,
     pub throw_target_height: f32,
     pub ui_fov: f32,
     pub fov: f32,
@@ -27,7 +29,8 @@ pub struct Config {
     pub mailbox_colors: Vec<Rgba<f32>>,
     pub double_mailbox_probability: f64,
     pub juggling_score_multiplier: f32,
-    pub deliver_score: f32,
+    pub color_match_bonus: f32,
+    pub color_mismatch_penalty: ColorMismatchPenalty,
     pub spawn_distance: f32,
     pub despawn_distance: f32,
     pub hand_rotation: f32,
@@ -49,15 +52,421 @@ pub struct Config {
     pub house_size: f32,
     pub multiplier_color: Rgba<f32>,
     pub max_throw_distance: f32,
+    pub distance_between_power_ups: f32,
+    pub power_up_size: f32,
+    pub power_up_color: Rgba<f32>,
+    pub slow_motion_time_scale: f32,
+    pub magnet_hand_radius: f32,
+    pub power_ups: Vec<PowerUpConfig>,
+    pub distance_between_hazards: f32,
+    pub hazard_ramp_time: f32,
+    /// Width of the bike in the middle of the road, hazards overlapping it are ridden over
+    pub bike_width: f32,
+    pub camera_shake_time: f32,
+    pub camera_shake_freq: f32,
+    pub hazards: Vec<HazardKind>,
+    pub endless: EndlessConfig,
+    pub combo: ComboConfig,
+    pub bot: BotConfig,
+    pub achievement_toast_time: f32,
+    pub race_progress_interval: f32,
+    pub achievements: Vec<Achievement>,
+    pub simulation_fps: f32,
+    pub max_simulation_steps: usize,
+    pub leaderboard: LeaderboardConfig,
+    pub items: Vec<ItemKind>,
+    /// Whether [ItemKind::mailboxes] are enforced
+    pub restricted_items: bool,
 
     pub time_scale: Vec<f32>,
     pub game_time: Vec<f32>,
     pub lives: Vec<usize>,
 }
 
-#[derive(PartialEq, Clone, Serialize)]
+impl Config {
+    /// Loads config.toml, checking what would otherwise panic in the middle of a run
+    pub async fn load(path: impl AsRef<std::path::Path>) -> anyhow::Result<Self> {
+        let config: Self = file::load_detect(path).await?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.mailbox_colors.is_empty() {
+            anyhow::bail!("mailbox_colors is empty");
+        }
+        for (name, len) in [
+            ("time_scale", self.time_scale.len()),
+            ("game_time", self.game_time.len()),
+            ("lives", self.lives.len()),
+        ] {
+            if len == 0 {
+                anyhow::bail!("{name} is empty");
+            }
+        }
+        validate_weights("items", self.items.iter().map(|kind| kind.weight))?;
+        for kind in &self.items {
+            if let Some(color) = kind
+                .mailboxes
+                .iter()
+                .find(|&&color| color >= self.mailbox_colors.len())
+            {
+                anyhow::bail!(
+                    "item {:?} is accepted by mailbox color {color}, but there are only {} colors",
+                    kind.name,
+                    self.mailbox_colors.len(),
+                );
+            }
+        }
+        // No power ups at all is fine, they are just not spawned then
+        if !self.power_ups.is_empty() {
+            validate_weights(
+                "power_ups",
+                self.power_ups.iter().map(|power_up| power_up.weight),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Whether a mailbox of this color takes items of this kind, see [Config::restricted_items]
+    pub fn accepts(&self, kind: usize, mailbox_color: usize) -> bool {
+        !self.restricted_items || self.items[kind].accepts(mailbox_color)
+    }
+
+    /// Mailbox colors that take items of this kind, any mailbox if empty
+    pub fn accepted_mailboxes(&self, kind: usize) -> &[usize] {
+        if self.restricted_items {
+            &self.items[kind].mailboxes
+        } else {
+            &[]
+        }
+    }
+}
+
+/// Weights of a random pick, at least one has to be positive
+fn validate_weights(name: &str, weights: impl Iterator<Item = f32>) -> anyhow::Result<()> {
+    let mut total = 0.0;
+    for weight in weights {
+        if !(weight.is_finite() && weight >= 0.0) {
+            anyhow::bail!("{name} has a weight of {weight}");
+        }
+        total += weight;
+    }
+    if total <= 0.0 {
+        anyhow::bail!("{name} has no positive weights");
+    }
+    Ok(())
+}
+
+/// Piecewise linear function of time given by `[time, value]` points sorted by time,
+/// constant before the first point and after the last one
+#[derive(Debug, Clone, Deserialize)]
+#[serde(transparent)]
+pub struct Curve(Vec<[f32; 2]>);
+
+impl Curve {
+    pub fn at(&self, time: f32) -> f32 {
+        let points = &self.0;
+        let next = points.partition_point(|&[point_time, _]| point_time <= time);
+        if next == 0 {
+            return points[0][1];
+        }
+        if next == points.len() {
+            return points[next - 1][1];
+        }
+        let [time_a, value_a] = points[next - 1];
+        let [time_b, value_b] = points[next];
+        value_a + (value_b - value_a) * (time - time_a) / (time_b - time_a)
+    }
+}
+
+/// Values that ramp over time in endless mode instead of the constant ones
+#[derive(Debug, Clone, Deserialize)]
+pub struct EndlessConfig {
+    pub ride_speed: Curve,
+    pub distance_between_mailboxes: Curve,
+    pub double_mailbox_probability: Curve,
+    pub gravity: Curve,
+    /// Seconds for the sky to go from day to evening
+    pub day_length: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ComboConfig {
+    pub deliveries_per_level: usize,
+    pub max_streak_multiplier: usize,
+    pub decay_time: f32,
+    pub pair_time: f32,
+    pub pair_bonus: f32,
+    /// Relative to [Config::mailbox_size]
+    pub perfect_radius: f32,
+    pub perfect_bonus: f32,
+}
+
+impl ComboConfig {
+    /// What delivery scores get multiplied by with this many deliveries in a row
+    pub fn streak_multiplier(&self, streak: usize) -> usize {
+        (1 + streak / self.deliveries_per_level).min(self.max_streak_multiplier)
+    }
+}
+
+/// How well the autoplay bot plays, see [crate::bot]
+#[derive(Debug, Clone, Deserialize)]
+pub struct BotConfig {
+    /// Seconds between the bot's actions
+    pub reaction_delay: f32,
+    /// Max distance the bot misses its targets by, in world units
+    pub aim_error: f32,
+    /// Items kept in the air before it stops pulling new ones from the bag
+    pub juggle_items: usize,
+    /// Seconds of idling in the main menu before the attract mode demo starts
+    pub attract_delay: f32,
+    /// Seconds after which headless runs are stopped, endless runs may go on forever otherwise
+    pub estimate_time_limit: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Achievement {
+    /// Shown to the player, also what unlocked achievements are saved by
+    pub name: String,
+    pub goal: AchievementGoal,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AchievementGoal {
+    Deliveries(usize),
+    Drops(usize),
+    /// Items in the air at once
+    Juggling(usize),
+    /// In a single run
+    Score(f32),
+    /// Runs finished without losing a life
+    FlawlessRuns(usize),
+    /// Different skins played with
+    Skins(usize),
+}
+
+/// Entry of the item catalog
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemKind {
+    pub name: String,
+    /// Name of a texture in `assets/items`, skin newspaper if not specified
+    pub texture: Option<String>,
+    /// Relative chance to be pulled from the bag
+    pub weight: f32,
+    /// Size relative to [Config::item_scale]
+    pub scale: f32,
+    pub gravity_scale: f32,
+    /// Max angular velocity when juggled
+    pub spin: f32,
+    /// Size of the hitbox relative to the drawn size
+    pub hitbox: f32,
+    pub score: f32,
+    /// Indices into [Config::mailbox_colors] that accept the item, any mailbox if empty.
+    /// Only enforced with [Config::restricted_items]
+    #[serde(default)]
+    pub mailboxes: Vec<usize>,
+}
+
+impl ItemKind {
+    pub fn accepts(&self, mailbox_color: usize) -> bool {
+        self.mailboxes.is_empty() || self.mailboxes.contains(&mailbox_color)
+    }
+}
+
+/// Something on the road that gets in the way
+#[derive(Debug, Clone, Deserialize)]
+pub struct HazardKind {
+    pub name: String,
+    /// Name of a texture in `assets/hazards`
+    pub texture: String,
+    pub size: f32,
+    /// Range of distance from the middle of the road, side is random
+    pub offset: [f32; 2],
+    /// Chance to spawn every [Config::distance_between_hazards] at the start of the run
+    pub spawn_chance: f32,
+    /// Chance to spawn after [Config::hazard_ramp_time]
+    pub max_spawn_chance: f32,
+    /// How much the camera shakes when riding over it, does not affect gameplay
+    #[serde(default)]
+    pub shake: f32,
+    /// Max sideways speed given to juggled items when riding over it
+    #[serde(default)]
+    pub knock: f32,
+    /// Whether mailboxes behind it on the same side of the road can not be thrown at
+    #[serde(default)]
+    pub blocks_throws: bool,
+    /// Speed along the road, same units as [Config::ride_speed]
+    #[serde(default)]
+    pub speed: f32,
+}
+
+impl HazardKind {
+    /// Chance to spawn after riding for `time` seconds
+    pub fn spawn_chance(&self, time: f32, ramp_time: f32) -> f32 {
+        let ramp = (time / ramp_time).clamp(0.0, 1.0);
+        self.spawn_chance + (self.max_spawn_chance - self.spawn_chance) * ramp
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerUpKind {
+    SlowMotion,
+    /// Restores a lost life, instant
+    ExtraLife,
+    DoubleMultiplier,
+    /// Makes mailboxes easier to aim at
+    Magnet,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PowerUpConfig {
+    pub kind: PowerUpKind,
+    /// Relative chance to be spawned
+    pub weight: f32,
+    /// How long the effect lasts, ignored for instant ones
+    #[serde(default)]
+    pub duration: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMismatchPenalty {
+    Bonus,
+    Life,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+/// Modes are all off by default
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
 pub struct Difficulty {
     pub time_scale: f32,
     pub game_time: f32,
     pub lives: usize,
+    /// Version of the scoring rules, see [Difficulty::RULES].
+    /// Scores from before the rules had versions have none, which is version 0
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub rules: u32,
+    /// Newspapers get mailbox colors and should be delivered to mailboxes of the same color.
+    /// Not serialized when off so that leaderboard meta stays the same as before
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub color_matching: bool,
+    /// No timer, the run ends when out of lives. [Difficulty::game_time] is 0 then
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub endless: bool,
+    /// No timer and no lives lost, the run only ends when leaving
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub practice: bool,
+    /// Two players on the same device sharing the road and lives
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub coop: bool,
+    /// Two players on the same device racing for the same mailboxes, each with their own lives
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub versus: bool,
+    /// Daily challenge of this date, see [crate::daily]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily: Option<Date>,
+}
+
+impl Difficulty {
+    /// Bumped when scoring changes, so that new scores get their own leaderboard categories
+    /// instead of being compared to the ones made by the old rules
+    pub const RULES: u32 = 1;
+
+    /// Picks from the lists in config by index, with all modes off.
+    /// `game_time` one past the end of [Config::game_time] is endless mode
+    pub fn new(config: &Config, time_scale: usize, game_time: usize, lives: usize) -> Self {
+        Self {
+            time_scale: config.time_scale[time_scale],
+            game_time: config.game_time.get(game_time).copied().unwrap_or(0.0),
+            lives: config.lives[lives],
+            rules: Self::RULES,
+            endless: game_time == config.game_time.len(),
+            ..default()
+        }
+    }
+
+    /// Same difficulty, ridden alone
+    pub fn solo(self) -> Self {
+        Self {
+            coop: false,
+            versus: false,
+            ..self
+        }
+    }
+
+    /// Whether the run ends when [Difficulty::game_time] runs out
+    pub fn timed(&self) -> bool {
+        !self.endless && !self.practice
+    }
+
+    pub fn players(&self) -> usize {
+        if self.coop || self.versus {
+            2
+        } else {
+            1
+        }
+    }
+
+    /// Index into [Config::game_time], one past the end for endless mode
+    pub fn game_time_index(&self, config: &Config) -> usize {
+        if self.endless {
+            return config.game_time.len();
+        }
+        config
+            .game_time
+            .iter()
+            .position(|x| *x == self.game_time)
+            .unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardBackendKind {
+    Jornet,
+    File,
+    Mock,
+}
+
+/// Who is playing, chosen in the main menu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub skin: String,
+}
+
+/// Settings of every backend are here, only the chosen one is used
+#[derive(Debug, Clone, Deserialize)]
+pub struct LeaderboardConfig {
+    pub backend: LeaderboardBackendKind,
+    /// Jornet server, official one if not specified
+    pub host: Option<String>,
+    pub id: String,
+    pub key: String,
+    /// Where the file backend keeps scores
+    pub path: std::path::PathBuf,
+}
+
+/// Per-launch settings coming from the command line rather than config.toml
+#[derive(Default)]
+pub struct Options {
+    pub seed: Option<u64>,
+    pub record_replay: Option<std::path::PathBuf>,
+    /// [None] if scores are only kept locally
+    pub leaderboard: Option<Rc<dyn leaderboard::LeaderboardBackend>>,
+    /// Relay server for online races, like `ws://127.0.0.1:1155`
+    pub relay: Option<String>,
+}
+
+impl Options {
+    /// Seed to start the next game with
+    pub fn game_seed(&self) -> u64 {
+        self.seed.unwrap_or_else(|| thread_rng().gen())
+    }
 }
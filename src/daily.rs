@@ -0,0 +1,78 @@
+//! Daily challenge: everyone playing on the same UTC date gets the same seed and difficulty
+
+use super::*;
+
+/// Date of the last daily challenge that got a scored attempt
+const PLAYED_KEY: &str = "daily_played";
+
+pub fn seed(date: Date) -> u64 {
+    date.year as u64 * 10000 + date.month as u64 * 100 + date.day as u64
+}
+
+/// Difficulty preset of the day, picked from the lists in config
+pub fn difficulty(config: &Config, date: Date) -> Difficulty {
+    let mut rng = StdRng::seed_from_u64(seed(date));
+    let time_scale = rng.gen_range(0..config.time_scale.len());
+    let game_time = rng.gen_range(0..config.game_time.len());
+    let lives = rng.gen_range(0..config.lives.len());
+    Difficulty {
+        color_matching: rng.gen(),
+        daily: Some(date),
+        ..Difficulty::new(config, time_scale, game_time, lives)
+    }
+}
+
+pub fn played(date: Date) -> bool {
+    preferences::load::<Date>(PLAYED_KEY) == Some(date)
+}
+
+/// Uses up the scored attempt of the day, called when the run is over or left
+pub fn finish(date: Date) {
+    preferences::save(PLAYED_KEY, &date);
+}
+
+/// Difficulty for the next attempt at the challenge, only the first one of the day is scored.
+/// Later attempts are practice runs with the same seed and preset
+pub fn attempt(config: &Config, date: Date) -> Difficulty {
+    Difficulty {
+        practice: played(date),
+        ..difficulty(config, date)
+    }
+}
+
+/// Difficulty and seed to play a run of `diff` again with
+pub fn play_again(config: &Config, options: &Options, diff: &Difficulty) -> (Difficulty, u64) {
+    match diff.daily {
+        Some(date) => (attempt(config, date), seed(date)),
+        None => (diff.clone(), options.game_seed()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::*;
+
+    #[test]
+    fn daily_challenge_is_the_same_all_day() {
+        let config = config();
+        let date = Date {
+            year: 2024,
+            month: 5,
+            day: 17,
+        };
+        let next_day = Date { day: 18, ..date };
+        assert_eq!(seed(date), seed(date));
+        assert_ne!(seed(date), seed(next_day));
+        let diff = difficulty(&config, date);
+        assert_eq!(diff, difficulty(&config, date));
+        assert_eq!(diff.daily, Some(date));
+        assert!(diff.timed());
+
+        // Separate leaderboard category every day
+        let meta = serde_json::to_string(&diff).unwrap();
+        assert!(meta.contains(r#""daily":{"year":2024,"month":5,"day":17}"#));
+        assert_eq!(serde_json::from_str::<Difficulty>(&meta).unwrap(), diff);
+    }
+}
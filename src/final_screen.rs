@@ -1,20 +1,35 @@
 use super::*;
 
+use leaderboard::SubmitError;
+use leaderboard_screen::LeaderboardScreen;
+
+#[derive(Debug, Clone)]
+pub struct FinishedRun {
+    pub diff: Difficulty,
+    /// Combined score of all players
+    pub score: f32,
+    /// Scores are submitted with the name of the first one
+    pub profiles: Vec<Profile>,
+    /// Part of the score every player made
+    pub player_scores: Vec<f32>,
+}
+
 pub struct FinalScreen {
     geng: Geng,
     assets: Rc<Assets>,
     config: Rc<Config>,
+    options: Rc<Options>,
 
     time_scale: usize,
     game_time: usize,
     lives: usize,
-    name: String,
-    global_place: Option<usize>,
+    run: FinishedRun,
+    /// Place among local high scores, shown when the leaderboard is unavailable
+    local_place: usize,
+    place: usize,
+    submit_error: Option<SubmitError>,
 
     transition: Option<geng::state::Transition>,
-
-    score: f32,
-    top10: Option<Vec<jornet::Score>>,
 }
 
 impl FinalScreen {
@@ -22,37 +37,70 @@ impl FinalScreen {
         geng: &Geng,
         assets: &Rc<Assets>,
         config: &Rc<Config>,
-        diff: Difficulty,
-        score: f32,
-        name: String,
-        pos_and_scores: Option<(usize, Vec<jornet::Score>)>,
+        options: &Rc<Options>,
+        run: FinishedRun,
+        local_place: usize,
+        submission: Option<Result<usize, SubmitError>>,
     ) -> Self {
-        let (global_place, top10) = match pos_and_scores {
-            Some((pos, scores)) => (Some(pos), Some(scores)),
-            None => (None, None),
+        let (place, submit_error) = match submission {
+            Some(Ok(place)) => (place, None),
+            submission => (local_place, submission.and_then(Result::err)),
         };
+        let diff = &run.diff;
         Self {
-            top10,
-            global_place,
-            name,
-            score,
+            local_place,
+            place,
+            submit_error,
             geng: geng.clone(),
             assets: assets.clone(),
             config: config.clone(),
+            options: options.clone(),
             time_scale: config
                 .time_scale
                 .iter()
                 .position(|x| *x == diff.time_scale)
                 .unwrap(),
-            game_time: config
-                .game_time
-                .iter()
-                .position(|x| *x == diff.game_time)
-                .unwrap(),
+            game_time: diff.game_time_index(config),
             lives: config.lives.iter().position(|x| *x == diff.lives).unwrap(),
+            run,
             transition: None,
         }
     }
+
+    /// Sends the score to the leaderboard if there is one, then shows the results
+    pub async fn submit(
+        geng: Geng,
+        assets: Rc<Assets>,
+        config: Rc<Config>,
+        options: Rc<Options>,
+        run: FinishedRun,
+        local_place: usize,
+    ) -> Self {
+        let submission = match &options.leaderboard {
+            Some(backend) => Some(
+                leaderboard::submit(
+                    &**backend,
+                    run.diff.clone(),
+                    &run.profiles[0].name,
+                    run.score,
+                )
+                .await,
+            ),
+            None => None,
+        };
+        if let Some(Err(e)) = &submission {
+            log::error!("{e}");
+        }
+        Self::new(
+            &geng,
+            &assets,
+            &config,
+            &options,
+            run,
+            local_place,
+            submission,
+        )
+    }
 }
 
 impl geng::State for FinalScreen {
@@ -68,29 +116,52 @@ impl geng::State for FinalScreen {
 
     fn ui<'a>(&'a mut self, cx: &'a geng::ui::Controller) -> Box<dyn geng::ui::Widget + 'a> {
         use geng::ui::*;
-        let game_time = ui::TextureWidget::new(&self.assets.difficulty.game_time[self.game_time]);
+        let game_time =
+            ui::TextureWidget::new(self.assets.difficulty.game_time_texture(self.game_time));
         let time_scale =
             ui::TextureWidget::new(&self.assets.difficulty.time_scale[self.time_scale]);
         let lives = ui::TextureWidget::new(&self.assets.difficulty.lives[self.lives]);
         let play = ui::TextureButton::new(cx, &self.assets.play_again, &self.assets.ui_sfx);
         if play.was_clicked() {
+            let (diff, seed) = daily::play_again(&self.config, &self.options, &self.run.diff);
             self.transition = Some(geng::state::Transition::Switch(Box::new(Game::new(
                 &self.geng,
                 &self.assets,
                 &self.config,
-                Difficulty {
-                    time_scale: self.config.time_scale[self.time_scale],
-                    game_time: self.config.game_time[self.game_time],
-                    lives: self.config.lives[self.lives],
-                },
-                self.name.clone(),
+                &self.options,
+                diff,
+                self.run.profiles.clone(),
+                seed,
+                RunMode::played(),
             ))));
         }
-        let score = ui::Text::new(&self.assets.font, (self.score.floor() as i32).to_string());
+        let score = ui::Text::new(
+            &self.assets.font,
+            (self.run.score.floor() as i32).to_string(),
+        );
         let menu = ui::TextureButton::new(cx, &self.assets.menu, &self.assets.ui_sfx);
         if menu.was_clicked() {
             self.transition = Some(geng::state::Transition::Pop);
         }
+        let place = ui::Text::new(&self.assets.font, (self.place + 1).to_string());
+        let leaderboard_button =
+            ui::TextureButton::new(cx, &self.assets.leaderboard_button, &self.assets.ui_sfx);
+        if leaderboard_button.was_clicked() {
+            self.transition = Some(geng::state::Transition::Push(Box::new(
+                geng::LoadingScreen::new(
+                    &self.geng,
+                    geng::EmptyLoadingScreen::new(&self.geng),
+                    LeaderboardScreen::load(
+                        self.geng.clone(),
+                        self.assets.clone(),
+                        self.config.clone(),
+                        self.options.clone(),
+                        self.run.diff.clone(),
+                        self.run.profiles[0].name.clone(),
+                    ),
+                ),
+            )));
+        }
         let mut stack = stack![
             ui::TextureWidget::new(&self.assets.final_screen),
             game_time.place(300, 95),
@@ -99,33 +170,64 @@ impl geng::State for FinalScreen {
             menu.place(25, 235),
             play.place(180, 220),
             score.fixed_size(vec2(0.0, 16.0)).place(90, 163),
+            place.fixed_size(vec2(0.0, 16.0)).place(53, 208),
+            leaderboard_button.place(70, 190),
         ];
-
-        #[cfg(feature = "leaderboard")]
-        {
-            if let Some(global_place) = self.global_place {
-                let global_place = ui::Text::new(&self.assets.font, (global_place + 1).to_string());
+        if self.run.diff.color_matching {
+            let colors = ui::Text::new(&self.assets.font, "colors".to_owned());
+            stack.push(Box::new(
+                colors.fixed_size(vec2(73.0, 10.0)).place(300, 208),
+            ));
+        }
+        if self.run.player_scores.len() > 1 {
+            for (index, score) in self.run.player_scores.iter().enumerate() {
+                let score = ui::Text::left_align(
+                    &self.assets.font,
+                    format!("p{} {}", index + 1, score.floor() as i32),
+                );
                 stack.push(Box::new(
-                    global_place.fixed_size(vec2(0.0, 16.0)).place(53, 208),
+                    score
+                        .fixed_size(vec2(0.0, 8.0))
+                        .place(190, 196 + index as i32 * 10),
                 ));
             }
-            if let Some(top10) = &self.top10 {
-                let leaderboard_button = ui::TextureButton::new(
-                    cx,
-                    &self.assets.leaderboard_button,
-                    &self.assets.ui_sfx,
-                );
-                if leaderboard_button.was_clicked() {
-                    self.transition = Some(geng::state::Transition::Push(Box::new(
-                        crate::leaderboard_screen::LeaderboardScreen::new(
-                            &self.geng,
-                            &self.assets,
-                            top10.clone(),
+        }
+        if let Some(date) = self.run.diff.daily {
+            let daily = ui::Text::new(&self.assets.font, format!("daily {date}"));
+            stack.push(Box::new(daily.fixed_size(vec2(73.0, 10.0)).place(300, 82)));
+        }
+
+        // Retrying after the score got sent would send it twice
+        if self
+            .submit_error
+            .as_ref()
+            .is_some_and(|e| !e.score_was_sent())
+        {
+            let error = ui::Text::left_align(&self.assets.font, "not submitted".to_owned());
+            stack.push(Box::new(error.fixed_size(vec2(0.0, 10.0)).place(12, 280)));
+            let retry = ui::TextButton::new(
+                cx,
+                &self.assets.font,
+                "retry".to_owned(),
+                &self.assets.ui_sfx,
+            );
+            if retry.was_clicked() {
+                self.transition = Some(geng::state::Transition::Switch(Box::new(
+                    geng::LoadingScreen::new(
+                        &self.geng,
+                        geng::EmptyLoadingScreen::new(&self.geng),
+                        Self::submit(
+                            self.geng.clone(),
+                            self.assets.clone(),
+                            self.config.clone(),
+                            self.options.clone(),
+                            self.run.clone(),
+                            self.local_place,
                         ),
-                    )));
-                }
-                stack.push(Box::new(leaderboard_button.place(70, 190)));
+                    ),
+                )));
             }
+            stack.push(Box::new(retry.fixed_size(vec2(50.0, 10.0)).place(150, 280)));
         }
 
         stack.center().boxed()
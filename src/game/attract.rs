@@ -0,0 +1,68 @@
+use super::*;
+
+use crate::bot::Bot;
+
+/// Demo run played by the bot after idling in the main menu, any input goes back to the menu
+pub struct AttractMode {
+    game: Game,
+    bot: Bot,
+    transition: Option<geng::state::Transition>,
+}
+
+impl AttractMode {
+    pub fn new(
+        geng: &Geng,
+        assets: &Rc<Assets>,
+        config: &Rc<Config>,
+        options: &Rc<Options>,
+        diff: Difficulty,
+        profile: Profile,
+        seed: u64,
+    ) -> Self {
+        let game = Game::new(
+            geng,
+            assets,
+            config,
+            options,
+            diff,
+            vec![profile],
+            seed,
+            RunMode::Watched,
+        );
+        Self {
+            game,
+            bot: Bot::new(&config.bot, seed),
+            transition: None,
+        }
+    }
+}
+
+impl geng::State for AttractMode {
+    fn handle_event(&mut self, event: geng::Event) {
+        if let geng::Event::KeyPress { .. }
+        | geng::Event::MousePress { .. }
+        | geng::Event::TouchStart(..) = event
+        {
+            self.transition = Some(geng::state::Transition::Pop);
+        }
+    }
+    fn update(&mut self, delta_time: f64) {
+        let bot = &mut self.bot;
+        let fixed_delta_time = 1.0 / self.game.config.simulation_fps;
+        self.game.fixed_update(delta_time as f32, |game| {
+            for input in bot.step(&game.simulation, fixed_delta_time) {
+                game.input(input);
+            }
+        });
+        if self.game.transition.take().is_some() {
+            // The demo is over, the final screen is for real runs
+            self.transition = Some(geng::state::Transition::Pop);
+        }
+    }
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        geng::State::draw(&mut self.game, framebuffer);
+    }
+    fn transition(&mut self) -> Option<geng::state::Transition> {
+        self.transition.take()
+    }
+}
@@ -2,14 +2,18 @@ use super::*;
 
 impl Game {
     pub fn draw_impl(&mut self, framebuffer: &mut ugli::Framebuffer) {
-        self.camera.latitude = self.my_latitude;
-        let progress = 1.0 - self.time_left / self.diff.game_time;
-
-        let skin_assets = self
-            .assets
-            .skins
-            .get(&self.name)
-            .unwrap_or(&self.assets.skins["default"]);
+        let alpha = self.interpolation_alpha();
+        let simulation = &self.simulation;
+        self.camera.latitude =
+            simulation.prev_latitude + (simulation.latitude - simulation.prev_latitude) * alpha;
+        self.camera.latitude += self.camera_shake
+            * (1.0 - self.camera_shake_t)
+            * (self.real_time * self.config.camera_shake_freq).sin();
+        let progress = if simulation.diff.timed() {
+            1.0 - simulation.time_left / simulation.diff.game_time
+        } else {
+            (simulation.time / self.config.endless.day_length).min(1.0)
+        };
 
         // Background
         ugli::clear(
@@ -47,39 +51,68 @@ impl Game {
             &self.assets.road,
         );
 
-        for item in &self.thrown_items {
-            let t = item.t / self.config.throw_time;
+        for item in &simulation.thrown_items {
+            let t = (item.prev_t + (item.t - item.prev_t) * alpha) / self.config.throw_time;
+            let (_, rot) = item.interpolated(alpha);
             let up = -vec3::cross(item.to - item.from, vec3(1.0, 0.0, 0.0)).normalize_or_zero();
             let pos = item.from
                 + (item.to - item.from) * t
                 + up * (1.0 - (1.0 - t * 2.0).sqr()) * self.config.throw_height;
             let matrix = mat4::translate(pos)
                 * mat4::rotate_x(Angle::from_radians(-self.camera.latitude - self.camera.rot))
-                * mat4::rotate_z(Angle::from_radians(item.rot))
+                * mat4::rotate_z(Angle::from_radians(rot))
                 * mat4::scale(item.half_size.extend(1.0) * self.config.item_throw_scale)
                 * mat4::translate(vec3(-1.0, -1.0, 0.0))
                 * mat4::scale_uniform(2.0);
+            let skin_assets = self.skin_assets(item.player);
             self.draw3d.draw_sprite_with_transform(
                 framebuffer,
                 &self.camera,
-                &item.texture,
+                self.assets
+                    .item_texture(&self.config.items[item.kind], skin_assets),
                 matrix,
-                item.color,
+                skin_assets.tinted(item.tint(&self.config)),
             );
         }
 
-        for mailbox in &self.mailboxes {
+        for mailbox in &simulation.mailboxes {
             self.draw3d.draw_sprite(
                 framebuffer,
                 &self.camera,
                 &self.assets.mailbox,
-                self.mailbox_pos(mailbox),
+                simulation.mailbox_pos(mailbox),
                 vec2::splat(self.config.mailbox_size) * vec2(-mailbox.x.signum(), 1.0),
                 self.config.mailbox_colors[mailbox.color],
             );
         }
 
-        for house in &self.houses {
+        for power_up in &simulation.power_ups {
+            self.draw3d.draw_sprite(
+                framebuffer,
+                &self.camera,
+                self.assets.power_ups.get(power_up.kind),
+                simulation.power_up_pos(power_up),
+                vec2::splat(self.config.power_up_size),
+                Rgba::WHITE,
+            );
+        }
+
+        for hazard in &simulation.hazards {
+            let kind = &self.config.hazards[hazard.kind];
+            let Some(texture) = self.assets.hazards.get(&kind.texture) else {
+                continue;
+            };
+            self.draw3d.draw_sprite(
+                framebuffer,
+                &self.camera,
+                texture,
+                simulation.hazard_pos(hazard),
+                vec2::splat(kind.size) * vec2(-hazard.x.signum(), 1.0),
+                Rgba::WHITE,
+            );
+        }
+
+        for house in &simulation.houses {
             self.draw3d.draw_sprite(
                 framebuffer,
                 &self.camera,
@@ -94,42 +127,139 @@ impl Game {
             );
         }
 
-        self.geng.draw2d().draw2d(
-            framebuffer,
-            self.camera.as_2d(),
-            &draw2d::TexturedQuad::new(self.bag_position, &skin_assets.bag),
-        );
-        self.geng.draw2d().draw2d(
-            framebuffer,
-            self.camera.as_2d(),
-            &draw2d::TexturedQuad::unit(&skin_assets.bike)
-                .translate(vec2(0.0, 1.0))
-                .scale_uniform(0.5)
-                .scale(self.bag_position.size() * vec2(2.0, 1.0))
-                .rotate(Angle::from_radians(self.real_time.sin() * 0.1))
-                .translate(vec2(self.bag_position.center().x, self.bag_position.min.y)),
-        );
-        for item in &self.juggling_items {
+        for (index, player) in simulation.players.iter().enumerate() {
+            let skin_assets = self.skin_assets(index);
+            // Next item peeking out of the bag, only if there is a choice
+            if simulation.next_color.is_some() || self.config.items.len() > 1 {
+                let kind = &self.config.items[simulation.next_kind];
+                self.geng.draw2d().draw2d(
+                    framebuffer,
+                    self.camera.as_2d(),
+                    &draw2d::TexturedQuad::unit_colored(
+                        self.assets.item_texture(kind, skin_assets),
+                        skin_assets.tinted(match simulation.next_color {
+                            Some(color) => self.config.mailbox_colors[color],
+                            None => Rgba::WHITE,
+                        }),
+                    )
+                    .scale(
+                        vec2(
+                            simulation.asset_info.item_aspects[simulation.next_kind],
+                            1.0,
+                        ) * self.config.item_scale
+                            * kind.scale
+                            * 0.7,
+                    )
+                    .translate(player.bag_position.center() + vec2(0.0, 0.5)),
+                );
+            }
             self.geng.draw2d().draw2d(
                 framebuffer,
                 self.camera.as_2d(),
-                &draw2d::TexturedQuad::unit_colored(&*item.texture, item.color)
+                &draw2d::TexturedQuad::colored(
+                    player.bag_position,
+                    &*skin_assets.bag,
+                    skin_assets.tint,
+                ),
+            );
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                self.camera.as_2d(),
+                &draw2d::TexturedQuad::unit_colored(&*skin_assets.bike, skin_assets.tint)
+                    .translate(vec2(0.0, 1.0))
+                    .scale_uniform(0.5 * skin_assets.scale)
+                    .scale(player.bag_position.size() * vec2(2.0, 1.0))
+                    .rotate(Angle::from_radians(self.real_time.sin() * 0.1))
+                    .translate(vec2(
+                        player.bag_position.center().x,
+                        player.bag_position.min.y,
+                    )),
+            );
+        }
+        // Ghost of the personal best, under everything the player handles
+        if let Some(ghost) = &self.ghost {
+            let skin_assets = self.assets.skins.get(&ghost.skin);
+            let half_width = self.camera.fov() / 2.0 * self.framebuffer_size.aspect();
+            let ghost_color = |color: Rgba<f32>| Rgba {
+                a: color.a * self.config.ghost_alpha,
+                ..color
+            };
+            for item in &ghost.simulation.juggling_items {
+                let (pos, rot) = item.interpolated(alpha);
+                self.geng.draw2d().draw2d(
+                    framebuffer,
+                    self.camera.as_2d(),
+                    &draw2d::TexturedQuad::unit_colored(
+                        self.assets
+                            .item_texture(&self.config.items[item.kind], skin_assets),
+                        ghost_color(skin_assets.tinted(item.tint(&self.config))),
+                    )
                     .scale(item.half_size)
-                    .rotate(Angle::from_radians(item.rot))
-                    .translate(item.pos),
+                    .rotate(Angle::from_radians(rot))
+                    .translate(pos),
+                );
+            }
+            for touch in &ghost.simulation.touches {
+                if let Some(item) = &touch.holding {
+                    self.geng.draw2d().draw2d(
+                        framebuffer,
+                        self.camera.as_2d(),
+                        &draw2d::TexturedQuad::unit_colored(
+                            self.assets
+                                .item_texture(&self.config.items[item.kind], skin_assets),
+                            ghost_color(skin_assets.tinted(item.tint(&self.config))),
+                        )
+                        .scale(item.half_size * self.config.item_hold_scale)
+                        .rotate(Angle::from_radians(item.rot))
+                        .translate(touch.position),
+                    );
+                }
+                self.geng.draw2d().draw2d(
+                    framebuffer,
+                    self.camera.as_2d(),
+                    &draw2d::TexturedQuad::unit_colored(
+                        if touch.holding.is_some() {
+                            &*skin_assets.holding_hand
+                        } else {
+                            &*skin_assets.hand
+                        },
+                        ghost_color(skin_assets.tint),
+                    )
+                    .rotate(Angle::from_radians(
+                        -touch.position.x / half_width * self.config.hand_rotation.to_radians(),
+                    ))
+                    .scale_uniform(self.config.hand_radius * skin_assets.scale)
+                    .translate(touch.position),
+                );
+            }
+        }
+        for item in &simulation.juggling_items {
+            let (pos, rot) = item.interpolated(alpha);
+            let skin_assets = self.skin_assets(item.player);
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                self.camera.as_2d(),
+                &draw2d::TexturedQuad::unit_colored(
+                    self.assets
+                        .item_texture(&self.config.items[item.kind], skin_assets),
+                    skin_assets.tinted(item.tint(&self.config)),
+                )
+                .scale(item.half_size)
+                .rotate(Angle::from_radians(rot))
+                .translate(pos),
             );
         }
-        for touch in &self.touches {
-            let cursor_world = self
-                .camera
-                .as_2d()
-                .screen_to_world(self.framebuffer_size, touch.position.map(|x| x as f32));
-            if self.bag_position.contains(cursor_world) {
+        for touch in &simulation.touches {
+            let cursor_world = touch.position;
+            let half_width = self.camera.fov() / 2.0 * self.framebuffer_size.aspect();
+            let skin_assets = self.skin_assets(touch.player);
+            let bag_position = simulation.players[touch.player].bag_position;
+            if bag_position.contains(cursor_world) {
                 self.geng.draw2d().draw2d(
                     framebuffer,
                     self.camera.as_2d(),
                     &draw2d::TexturedQuad::new(
-                        self.bag_position.extend_uniform(0.1),
+                        bag_position.extend_uniform(0.1),
                         &self.assets.envelope_highlight,
                     ),
                 );
@@ -138,21 +268,26 @@ impl Game {
                 self.geng.draw2d().draw2d(
                     framebuffer,
                     self.camera.as_2d(),
-                    &draw2d::TexturedQuad::unit_colored(&*item.texture, item.color)
-                        .scale(item.half_size * self.config.item_hold_scale)
-                        .rotate(Angle::from_radians(item.rot))
-                        .translate(cursor_world),
+                    &draw2d::TexturedQuad::unit_colored(
+                        self.assets
+                            .item_texture(&self.config.items[item.kind], skin_assets),
+                        skin_assets.tinted(item.tint(&self.config)),
+                    )
+                    .scale(item.half_size * self.config.item_hold_scale)
+                    .rotate(Angle::from_radians(item.rot))
+                    .translate(cursor_world),
                 );
             }
-            if let Some(index) = self.hovered_item(touch.position) {
-                let item = &self.juggling_items[index];
+            if let Some(index) = simulation.hovered_item(touch.position) {
+                let item = &simulation.juggling_items[index];
+                let (pos, rot) = item.interpolated(alpha);
                 self.geng.draw2d().draw2d(
                     framebuffer,
                     self.camera.as_2d(),
                     &draw2d::TexturedQuad::unit(&self.assets.envelope_highlight)
                         .scale(item.half_size * 1.1)
-                        .rotate(Angle::from_radians(item.rot))
-                        .translate(item.pos),
+                        .rotate(Angle::from_radians(rot))
+                        .translate(pos),
                 );
             }
 
@@ -161,9 +296,9 @@ impl Game {
                 self.camera.as_2d(),
                 &draw2d::TexturedQuad::unit_colored(
                     if touch.holding.is_some() {
-                        &skin_assets.holding_hand
+                        &*skin_assets.holding_hand
                     } else {
-                        &skin_assets.hand
+                        &*skin_assets.hand
                     },
                     Rgba::new(0.0, 0.0, 0.0, 0.1),
                 )
@@ -174,10 +309,9 @@ impl Game {
                 //     0.0,
                 // ))
                 .rotate(Angle::from_radians(
-                    (1.0 - touch.position.x as f32 / self.framebuffer_size.x * 2.0)
-                        * self.config.hand_rotation.to_radians(),
+                    -touch.position.x / half_width * self.config.hand_rotation.to_radians(),
                 ))
-                .scale_uniform(self.config.hand_radius)
+                .scale_uniform(self.config.hand_radius * skin_assets.scale)
                 .translate(cursor_world),
             );
             self.geng.draw2d().draw2d(
@@ -185,14 +319,14 @@ impl Game {
                 self.camera.as_2d(),
                 &draw2d::TexturedQuad::unit_colored(
                     if touch.holding.is_some() {
-                        &skin_assets.holding_hand
+                        &*skin_assets.holding_hand
                     } else {
-                        &skin_assets.hand
+                        &*skin_assets.hand
                     },
                     if touch.error_animation_time < 1.0 {
                         Rgba::RED
                     } else {
-                        Rgba::WHITE
+                        skin_assets.tint
                     },
                 )
                 .translate(vec2(
@@ -202,10 +336,9 @@ impl Game {
                     0.0,
                 ))
                 .rotate(Angle::from_radians(
-                    (1.0 - touch.position.x as f32 / self.framebuffer_size.x * 2.0)
-                        * self.config.hand_rotation.to_radians(),
+                    -touch.position.x / half_width * self.config.hand_rotation.to_radians(),
                 ))
-                .scale_uniform(self.config.hand_radius)
+                .scale_uniform(self.config.hand_radius * skin_assets.scale)
                 .translate(
                     cursor_world
                         + (vec2(0.0, self.config.throw_target_height) - cursor_world)
@@ -214,11 +347,28 @@ impl Game {
                 ),
             );
 
-            if let Some(index) = self.hovered_mailbox(touch.position) {
-                let mailbox = &self.mailboxes[index];
+            // Mailboxes are aimed at first, same as when throwing
+            let aim = match simulation.hovered_mailbox(touch.position) {
+                Some(index) => {
+                    let mailbox = &simulation.mailboxes[index];
+                    // Red if the mailbox would reject the held item
+                    let accepted = touch
+                        .holding
+                        .as_ref()
+                        .map_or(true, |item| self.config.accepts(item.kind, mailbox.color));
+                    let pos = simulation.mailbox_pos(mailbox);
+                    Some((pos, self.config.mailbox_size, accepted))
+                }
+                None => simulation.hovered_power_up(touch.position).map(|index| {
+                    let power_up = &simulation.power_ups[index];
+                    let pos = simulation.power_up_pos(power_up);
+                    (pos, self.config.power_up_size, true)
+                }),
+            };
+            if let Some((pos, size, accepted)) = aim {
                 let camera_up =
                     vec3::cross(self.camera.dir(), vec3(1.0, 0.0, 0.0)).normalize_or_zero();
-                let pos = self.mailbox_pos(mailbox) + camera_up * self.config.mailbox_size * 0.75;
+                let pos = pos + camera_up * size * 0.75;
                 if let Some(pos) = self.camera.world_to_screen(self.framebuffer_size, pos) {
                     let pos = self
                         .camera
@@ -227,10 +377,13 @@ impl Game {
                     self.geng.draw2d().draw2d(
                         framebuffer,
                         self.camera.as_2d(),
-                        &draw2d::TexturedQuad::unit(&self.assets.aim)
-                            .scale_uniform(1.0)
-                            .rotate(Angle::from_radians(self.real_time))
-                            .translate(pos),
+                        &draw2d::TexturedQuad::unit_colored(
+                            &self.assets.aim,
+                            if accepted { Rgba::WHITE } else { Rgba::RED },
+                        )
+                        .scale_uniform(1.0)
+                        .rotate(Angle::from_radians(self.real_time))
+                        .translate(pos),
                     );
                 }
             }
@@ -238,48 +391,106 @@ impl Game {
 
         self.draw_particles(framebuffer);
 
-        for i in 0..self.diff.lives {
-            const W: f32 = 2.0;
-            let pos = vec2(
-                -W / 2.0 + W * i as f32 / (self.diff.lives.max(2) - 1) as f32,
-                4.5,
+        // Lives are never lost in practice mode,
+        // in versus mode every player has their own above their bag
+        let rows = if simulation.diff.practice {
+            vec![]
+        } else if simulation.diff.versus {
+            simulation
+                .players
+                .iter()
+                .map(|player| (player.bag_position.center().x, player.lives))
+                .collect()
+        } else {
+            vec![(0.0, simulation.lives)]
+        };
+        for (center, lives) in rows {
+            for i in 0..simulation.diff.lives {
+                const W: f32 = 2.0;
+                let pos = vec2(
+                    center - W / 2.0 + W * i as f32 / (simulation.diff.lives.max(2) - 1) as f32,
+                    4.5,
+                );
+                self.geng.draw2d().draw2d(
+                    framebuffer,
+                    self.camera.as_2d(),
+                    &draw2d::TexturedQuad::new(
+                        Aabb2::point(pos).extend_uniform(0.4),
+                        if i < lives {
+                            &self.assets.heart
+                        } else {
+                            &self.assets.cross
+                        },
+                    ),
+                );
+            }
+        }
+
+        // Versus players only score with their own items, so each gets a multiplier by the lives
+        if simulation.diff.versus {
+            for (index, player) in simulation.players.iter().enumerate() {
+                self.assets.font.draw(
+                    framebuffer,
+                    self.camera.as_2d(),
+                    &format!("x{}", simulation.multiplier(Some(index))),
+                    Rgba::BLACK,
+                    mat3::translate(vec2(player.bag_position.center().x + 1.6, 4.25))
+                        * mat3::scale_uniform(0.5),
+                );
+            }
+        }
+
+        let top_right = self
+            .camera
+            .as_2d()
+            .screen_to_world(self.framebuffer_size, self.framebuffer_size);
+        if simulation.diff.timed() {
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                self.camera.as_2d(),
+                &draw2d::TexturedQuad::unit(&self.assets.timer)
+                    .translate(top_right - vec2(1.5, 1.5)),
             );
             self.geng.draw2d().draw2d(
                 framebuffer,
                 self.camera.as_2d(),
-                &draw2d::TexturedQuad::new(
-                    Aabb2::point(pos).extend_uniform(0.4),
-                    if i < self.lives {
-                        &self.assets.heart
-                    } else {
-                        &self.assets.cross
-                    },
-                ),
+                &draw2d::TexturedQuad::unit(&self.assets.timer_arrow)
+                    .rotate(Angle::from_radians(-2.0 * f32::PI * progress))
+                    .translate(top_right - vec2(1.5, 1.5)),
             );
         }
 
-        let top_right = self
-            .camera
-            .as_2d()
-            .screen_to_world(self.framebuffer_size, self.framebuffer_size);
-        self.geng.draw2d().draw2d(
-            framebuffer,
-            self.camera.as_2d(),
-            &draw2d::TexturedQuad::unit(&self.assets.timer).translate(top_right - vec2(1.5, 1.5)),
-        );
-        self.geng.draw2d().draw2d(
-            framebuffer,
-            self.camera.as_2d(),
-            &draw2d::TexturedQuad::unit(&self.assets.timer_arrow)
-                .rotate(Angle::from_radians(-2.0 * f32::PI * progress))
-                .translate(top_right - vec2(1.5, 1.5)),
-        );
+        // Stats overlay in place of the timer
+        if simulation.diff.practice {
+            let stats = &simulation.stats;
+            let accuracy = match stats.accuracy() {
+                Some(accuracy) => ((accuracy * 100.0).round() as i32).to_string(),
+                None => "-".to_owned(),
+            };
+            let lines = [
+                format!("drops: {}", stats.drops),
+                format!("accuracy: {accuracy}"),
+                format!("streak: {}", stats.longest_juggle_streak),
+            ];
+            for (i, line) in lines.iter().enumerate() {
+                const SCALE: f32 = 0.4;
+                self.assets.font.draw(
+                    framebuffer,
+                    self.camera.as_2d(),
+                    line,
+                    Rgba::BLACK,
+                    mat3::translate(
+                        top_right - vec2(line.len() as f32 * SCALE + 0.5, 1.0 + i as f32 * 0.6),
+                    ) * mat3::scale_uniform(SCALE),
+                );
+            }
+        }
 
         let top_left = self
             .camera
             .as_2d()
             .screen_to_world(self.framebuffer_size, vec2(0.0, self.framebuffer_size.y));
-        let score_text = (self.score.floor() as i32).to_string();
+        let score_text = (simulation.score.floor() as i32).to_string();
         self.geng.draw2d().draw_textured(
             framebuffer,
             self.camera.as_2d(),
@@ -321,17 +532,7 @@ impl Game {
             mat3::translate(top_left + vec2(0.5, -1.5)),
         );
 
-        let multiplier_text = format!(
-            "x{}",
-            // TODO this is copypasta 🍝
-            self.juggling_items.len()
-                + 1
-                + self
-                    .touches
-                    .iter()
-                    .filter(|touch| touch.holding.is_some())
-                    .count()
-        );
+        let multiplier_text = format!("x{}", simulation.multiplier(None));
         self.geng.draw2d().draw_textured(
             framebuffer,
             self.camera.as_2d(),
@@ -373,14 +574,105 @@ impl Game {
             mat3::translate(top_left + vec2(0.5, -2.5)) * mat3::scale_uniform(0.5),
         );
 
+        // Ghost delta and race rivals are listed to the right of the score, a row each
+        let column_x = score_text.len() as f32 + 2.0;
+        let mut row_y = -1.0;
+        if let Some(ghost) = &self.ghost {
+            let delta = (simulation.score - ghost.simulation.score).floor() as i32;
+            self.assets.font.draw(
+                framebuffer,
+                self.camera.as_2d(),
+                &format!("{delta:+}"),
+                Rgba::new(0.0, 0.0, 0.0, 0.5),
+                mat3::translate(top_left + vec2(column_x, -1.4)) * mat3::scale_uniform(0.5),
+            );
+            row_y = -1.9;
+        }
+
+        if let Some(race) = &self.race {
+            const SCALE: f32 = 0.4;
+            for (i, rival) in race.rivals.values().enumerate() {
+                let mut text = format!("{} {}", rival.name, rival.score.floor() as i32);
+                if rival.finished {
+                    text += " done";
+                } else if let Some((score, time)) = rival.last_delivery {
+                    if time < 1.0 {
+                        text += &format!(" {:+}", score.floor() as i32);
+                    }
+                }
+                self.assets.font.draw(
+                    framebuffer,
+                    self.camera.as_2d(),
+                    &text,
+                    Rgba::new(0.0, 0.0, 0.0, 0.5),
+                    mat3::translate(top_left + vec2(column_x, row_y - i as f32 * 0.5))
+                        * mat3::scale_uniform(SCALE),
+                );
+            }
+        }
+
         if self.last_score_t < 1.0 {
+            let pos = if simulation.diff.versus {
+                let player = self.last_score_player;
+                let bag_center = simulation.players[player].bag_position.center();
+                let multiplier_text = format!("x{}", simulation.multiplier(Some(player)));
+                vec2(
+                    bag_center.x + 2.1 + multiplier_text.len() as f32 * 0.5,
+                    4.25,
+                )
+            } else {
+                top_left + vec2(multiplier_text.len() as f32 * 0.5 + 2.0, -2.5)
+            };
+            let color = Rgba::new(0.0, 0.0, 0.0, 1.0 - self.last_score_t);
             self.assets.font.draw(
                 framebuffer,
                 self.camera.as_2d(),
                 &self.last_score_text,
-                Rgba::new(0.0, 0.0, 0.0, 1.0 - self.last_score_t),
-                mat3::translate(top_left + vec2(multiplier_text.len() as f32 * 0.5 + 2.0, -2.5))
-                    * mat3::scale_uniform(0.5),
+                color,
+                mat3::translate(pos) * mat3::scale_uniform(0.5),
+            );
+            self.assets.font.draw(
+                framebuffer,
+                self.camera.as_2d(),
+                &self.last_combo_text,
+                color,
+                mat3::translate(pos + vec2((self.last_score_text.len() + 1) as f32 * 0.5, 0.0))
+                    * mat3::scale_uniform(0.4),
+            );
+        }
+
+        // Active effects with their remaining time under the multiplier
+        for (i, effect) in simulation.effects.iter().enumerate() {
+            let pos = top_left + vec2(0.5, -3.2 - i as f32 * 0.6);
+            self.geng.draw2d().draw2d(
+                framebuffer,
+                self.camera.as_2d(),
+                &draw2d::TexturedQuad::new(
+                    Aabb2::point(pos + vec2(0.25, 0.2)).extend_uniform(0.25),
+                    self.assets.power_ups.get(effect.kind),
+                ),
+            );
+            self.assets.font.draw(
+                framebuffer,
+                self.camera.as_2d(),
+                &(effect.time_left.ceil() as i32).to_string(),
+                Rgba::BLACK,
+                mat3::translate(pos + vec2(0.7, 0.0)) * mat3::scale_uniform(0.4),
+            );
+        }
+
+        if let Some(name) = self.toasts.front() {
+            let text = format!("achievement: {name}");
+            const SCALE: f32 = 0.5;
+            // Fade in and out
+            let alpha = (1.0 - (self.toast_t * 2.0 - 1.0).abs()) * 4.0;
+            self.assets.font.draw(
+                framebuffer,
+                self.camera.as_2d(),
+                &text,
+                Rgba::new(0.0, 0.0, 0.0, alpha.min(1.0)),
+                mat3::translate(vec2(-text.len() as f32 * SCALE / 2.0, 3.5))
+                    * mat3::scale_uniform(SCALE),
             );
         }
 
@@ -0,0 +1,46 @@
+use super::*;
+
+use replay::ReplayEvent;
+
+/// Saved run played back next to the current one, see [crate::ghosts]
+pub struct Ghost {
+    pub simulation: Simulation,
+    /// Skin of the player who made the run
+    pub skin: String,
+    events: Vec<ReplayEvent>,
+    next_event: usize,
+}
+
+impl Ghost {
+    /// [None] if the run would not play back the same
+    pub fn new(assets: &Assets, config: &Rc<Config>, replay: Replay) -> Option<Self> {
+        if replay.simulation_fps != config.simulation_fps {
+            return None;
+        }
+        let skin_assets = assets.skins.get(&replay.profile.skin);
+        Some(Self {
+            simulation: Simulation::new(
+                config,
+                replay.diff,
+                replay.seed,
+                asset_info(assets, config, skin_assets),
+            ),
+            skin: replay.profile.skin,
+            events: replay.events,
+            next_event: 0,
+        })
+    }
+
+    /// Same as a step of the game, with inputs coming from the saved run
+    pub fn update(&mut self, delta_time: f32) {
+        while let Some(event) = self.events.get(self.next_event) {
+            if event.tick > self.simulation.tick {
+                break;
+            }
+            self.simulation.input(event.input.clone());
+            self.next_event += 1;
+        }
+        self.simulation.update(delta_time);
+        self.simulation.take_events();
+    }
+}
@@ -1,204 +1,204 @@
 use super::*;
 
-type Id = usize;
+use crate::race::Race;
+use crate::simulation::{self, AssetInfo, Simulation, TouchInput, TouchKind};
 
+mod attract;
 mod controls;
 mod draw;
+mod ghost;
 mod particle;
+mod replay;
 mod update;
 
+pub use attract::AttractMode;
+use ghost::Ghost;
 use particle::*;
+pub use replay::{Replay, ReplayState};
 
-struct Item {
-    texture: Rc<Texture>,
-    pos: vec2<f32>,
-    vel: vec2<f32>,
-    rot: f32,
-    w: f32,
-    half_size: vec2<f32>,
-    color: Rgba<f32>,
+/// Whether somebody plays the run or it is only watched
+#[derive(Clone)]
+pub enum RunMode {
+    /// Recorded, raced against the personal best ghost and counted for achievements and the daily challenge.
+    /// Restarts share the achievement progress, so that the counters of the previous attempt are kept
+    Played(Rc<RefCell<achievements::Progress>>),
+    /// Replays and the attract mode demo
+    Watched,
 }
 
-impl Item {
-    pub fn new(texture: &Rc<Texture>, scale: f32) -> Self {
-        Self {
-            texture: texture.clone(),
-            pos: vec2::ZERO,
-            vel: vec2::ZERO,
-            rot: thread_rng().gen_range(0.0..2.0 * f32::PI),
-            w: 0.0,
-            half_size: vec2(texture.size().map(|x| x as f32).aspect(), 1.0) * scale,
-            color: {
-                Rgba::new(
-                    thread_rng().gen_range(0.9..1.0),
-                    thread_rng().gen_range(0.9..1.0),
-                    thread_rng().gen_range(0.9..1.0),
-                    1.0,
-                )
-            },
-        }
+impl RunMode {
+    /// Played with the achievement progress saved on this device
+    pub fn played() -> Self {
+        Self::Played(Rc::new(RefCell::new(achievements::Progress::load())))
     }
 }
 
-#[derive(Deref)]
-struct ThrownItem {
-    #[deref]
-    pub item: Item,
-    pub from: vec3<f32>,
-    pub to: vec3<f32>,
-    pub t: f32,
-    pub to_id: Id,
-}
-
-struct Mailbox {
-    pub id: Id,
-    pub x: f32,
-    pub latitude: f32,
-    pub color: usize,
-}
-
-struct House {
-    pub x: f32,
-    pub latitude: f32,
-    pub texture: usize,
-}
-
-struct Touch {
-    id: Option<u64>,
-    position: vec2<f32>,
-    holding: Option<Item>,
-    error_animation_time: f32,
-    throw_animation_time: f32,
-    remove_time: Option<f32>,
-}
-
+/// Presents a [Simulation]: draws it, plays sounds and handles window input
 pub struct Game {
-    name: String,
-    diff: Difficulty,
+    /// One for every player in [Simulation::players]
+    profiles: Vec<Profile>,
+    mode: RunMode,
+    simulation: Simulation,
+    rng: StdRng,
+    recording: Replay,
+    /// Where [Game::recording] gets saved, taken once it is so that a run is saved only once
+    record_replay: Option<std::path::PathBuf>,
+    /// Daily challenge whose scored attempt this run is, taken once the attempt is used up
+    daily_attempt: Option<Date>,
     real_time: f32,
-    score: f32,
-    time_left: f32,
-    next_id: Id,
+    accumulated_time: f32,
     framebuffer_size: vec2<f32>,
     geng: Geng,
     assets: Rc<Assets>,
     config: Rc<Config>,
+    options: Rc<Options>,
     camera: Camera,
-    juggling_items: Vec<Item>,
-    thrown_items: Vec<ThrownItem>,
-    bag_position: Aabb2<f32>,
-    mailboxes: Vec<Mailbox>,
-    houses: Vec<House>,
     draw3d: Draw3d,
-    my_latitude: f32,
     road_mesh: ugli::VertexBuffer<draw3d::Vertex>,
     transition: Option<geng::state::Transition>,
-    lives: usize,
-    touches: Vec<Touch>,
     music: geng::SoundEffect,
     particles_ui: Vec<Particle>,
     particles_3d: Vec<Particle>,
     last_score_text: String,
     last_score_t: f32,
+    /// Shown next to [Game::last_score_text] when the delivery built a combo
+    last_combo_text: String,
+    /// Who made the last delivery, versus players get the pop-up next to their own multiplier
+    last_score_player: usize,
+    camera_shake: f32,
+    camera_shake_t: f32,
     end_timer: f32,
     lose_sfx: Option<geng::SoundEffect>,
     cursor_pos: vec2<f32>,
+    /// Cursor of the second player in co-op or versus, in world coordinates
+    keyboard_cursor: vec2<f32>,
+    /// [None] when achievements should not be tracked, like for replays
+    achievements: Option<Rc<RefCell<achievements::Progress>>>,
+    /// Names of unlocked achievements to show, the first one is being shown
+    toasts: std::collections::VecDeque<String>,
+    toast_t: f32,
+    /// Online race this run is a part of
+    race: Option<Race>,
+    /// Personal best on the same seed and difficulty
+    ghost: Option<Ghost>,
 }
 
 impl Drop for Game {
     fn drop(&mut self) {
         self.geng.window().unlock_cursor();
         self.music.stop();
+        // Runs left before they are over, like every practice run, are saved here
+        self.save_replay();
+        // Leaving the daily challenge uses up the attempt too
+        self.finish_daily();
+        if let Some(achievements) = &self.achievements {
+            achievements.borrow().save();
+        }
         if let Some(mut sfx) = self.lose_sfx.take() {
             sfx.stop();
         }
     }
 }
 
-impl Game {
-    fn mailbox_pos(&self, mailbox: &Mailbox) -> vec3<f32> {
-        let circle_pos =
-            vec2(self.config.earth_radius, 0.0).rotate(Angle::from_radians(mailbox.latitude));
-        vec3(mailbox.x, circle_pos.x, -circle_pos.y)
+/// Item sizes depend on the textures of the skin
+fn asset_info(assets: &Assets, config: &Config, skin_assets: &Skin) -> AssetInfo {
+    AssetInfo {
+        item_aspects: config
+            .items
+            .iter()
+            .map(|kind| {
+                let texture = assets.item_texture(kind, skin_assets);
+                texture.size().map(|x| x as f32).aspect()
+            })
+            .collect(),
+        house_textures: assets.houses.len(),
     }
-    fn hovered_mailbox(&self, cursor: vec2<f32>) -> Option<usize> {
-        // self.hovered_mailbox();
-        let ray = self.camera.pixel_ray(self.framebuffer_size, cursor);
-        let camera_dir = self.camera.dir();
-        let right = vec3(1.0, 0.0, 0.0);
-        let up = vec3::cross(camera_dir, right).normalize_or_zero();
-        self.mailboxes.iter().position(|mailbox| {
-            let pos = self.mailbox_pos(mailbox);
-            // dot(ray.from + ray.dir * t - pos, camera_dir) = 0
-            let t = vec3::dot(pos - ray.from, camera_dir) / vec3::dot(ray.dir, camera_dir);
-            if t < 0.0 || t * ray.dir.len() > self.config.max_throw_distance {
-                return false;
-            }
-            let p = ray.from + ray.dir * t;
-
-            // let p = vec2(vec3::dot(p - pos, right), vec3::dot(p - pos, up));
-            // Aabb2::ZERO
-            //     .extend_symmetric(vec2(self.config.mailbox_size / 2.0, 0.0))
-            //     .extend_up(self.config.mailbox_size)
-            //     .extend_uniform(self.config.hand_radius)
-            //     .contains(p)
-
-            let center = pos + up * self.config.mailbox_size / 2.0;
-            let Some(center) = self.camera.world_to_screen(self.framebuffer_size, center) else {
-                return false;
-            };
-            let Some(pos) = self.camera.world_to_screen(self.framebuffer_size, pos) else {
-                return false;
-            };
-            let size = (center - pos).len();
+}
 
-            Aabb2::point(center)
-                .extend_uniform(size)
-                .extend_uniform(
-                    self.config.hand_radius / self.config.ui_fov * self.framebuffer_size.y,
-                )
-                .contains(cursor)
-        })
-    }
+impl Game {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         geng: &Geng,
         assets: &Rc<Assets>,
         config: &Rc<Config>,
+        options: &Rc<Options>,
         diff: Difficulty,
-        name: String,
+        profiles: Vec<Profile>,
+        seed: u64,
+        mode: RunMode,
     ) -> Self {
+        log::info!("Starting game with seed {seed}");
         let camera = Camera::new(
             config.fov.to_radians(),
             config.ui_fov,
             config.camera_rot.to_radians(),
             config.earth_radius + config.camera_height,
         );
+        let skin_assets = assets.skins.get(&profiles[0].skin);
+        let simulation = Simulation::new(
+            config,
+            diff.clone(),
+            seed,
+            asset_info(assets, config, skin_assets),
+        );
+        let played = matches!(mode, RunMode::Played(..));
+        let achievements = match &mode {
+            RunMode::Played(achievements) => {
+                achievements
+                    .borrow_mut()
+                    .skins
+                    .insert(profiles[0].skin.clone());
+                Some(achievements.clone())
+            }
+            RunMode::Watched => None,
+        };
         let mut music = assets.music.play();
         music.set_volume(0.4);
         Self {
             cursor_pos: vec2::ZERO,
-            name,
+            achievements,
+            toasts: default(),
+            toast_t: 0.0,
+            race: None,
+            ghost: if played {
+                ghosts::load(options, seed, &diff)
+                    .and_then(|replay| Ghost::new(assets, config, replay))
+            } else {
+                None
+            },
+            keyboard_cursor: match simulation.players.get(1) {
+                Some(player) => player.bag_position.center() + vec2(0.0, 2.0),
+                None => vec2::ZERO,
+            },
+            record_replay: options.record_replay.clone().filter(|_| played),
+            daily_attempt: diff.daily.filter(|_| played && !diff.practice),
+            recording: Replay {
+                seed,
+                diff,
+                profile: profiles[0].clone(),
+                partner: profiles.get(1).cloned(),
+                simulation_fps: config.simulation_fps,
+                events: vec![],
+                score: None,
+            },
+            profiles,
+            mode,
+            simulation,
+            // Separate from the simulation so that visual effects do not affect gameplay
+            rng: StdRng::seed_from_u64(seed),
             lose_sfx: None,
             end_timer: 0.0,
-            diff: diff.clone(),
-            houses: vec![],
             real_time: 0.0,
+            accumulated_time: 0.0,
             music,
-            lives: diff.lives,
-            score: 0.0,
-            time_left: diff.game_time,
             transition: None,
-            next_id: 0,
             framebuffer_size: vec2::splat(1.0),
             geng: geng.clone(),
             assets: assets.clone(),
             config: config.clone(),
-            bag_position: Aabb2::point(vec2(0.0, -camera.fov() / 2.0 + 1.0)).extend_uniform(1.0),
+            options: options.clone(),
             camera,
-            juggling_items: vec![],
-            mailboxes: vec![],
             draw3d: Draw3d::new(geng, assets),
-            my_latitude: 0.0,
             road_mesh: ugli::VertexBuffer::new_static(geng.ugli(), {
                 const N: usize = 100;
                 (0..=N)
@@ -214,22 +214,54 @@ impl Game {
                     })
                     .collect()
             }),
-            thrown_items: vec![],
-            touches: vec![],
             particles_3d: vec![],
             particles_ui: vec![],
             last_score_t: 1.0,
             last_score_text: "".to_owned(),
+            last_combo_text: "".to_owned(),
+            last_score_player: 0,
+            camera_shake: 0.0,
+            camera_shake_t: 1.0,
+        }
+    }
+
+    /// Makes this run a part of an online race, reporting progress to the rivals
+    pub fn with_race(mut self, race: Race) -> Self {
+        self.race = Some(race);
+        self
+    }
+
+    /// Skin of the player with the given index in [Simulation::players]
+    fn skin_assets(&self, player: usize) -> &Skin {
+        let profile = self.profiles.get(player).unwrap_or(&self.profiles[0]);
+        self.assets.skins.get(&profile.skin)
+    }
+
+    fn save_replay(&mut self) {
+        if let Some(path) = self.record_replay.take() {
+            self.recording.save(&path);
+        }
+    }
+
+    fn finish_daily(&mut self) {
+        if let Some(date) = self.daily_attempt.take() {
+            daily::finish(date);
         }
     }
 
     fn restart(&mut self) {
+        // So that the next attempt is a practice run
+        self.finish_daily();
+        let (diff, seed) = daily::play_again(&self.config, &self.options, &self.simulation.diff);
         self.transition = Some(geng::state::Transition::Switch(Box::new(Game::new(
             &self.geng,
             &self.assets,
             &self.config,
-            self.diff.clone(),
-            self.name.clone(),
+            &self.options,
+            diff,
+            self.profiles.clone(),
+            seed,
+            self.mode.clone(),
         ))));
     }
 }
@@ -243,25 +275,25 @@ impl geng::State for Game {
                         // self.geng.window().lock_cursor();
                         self.cursor_pos = position.map(|x| x as f32);
                     }
-                    self.touch_start(None, self.cursor_pos);
+                    self.screen_input(TouchKind::Start, None, self.cursor_pos);
                 }
             }
             geng::Event::RawMouseMove { delta } => {
                 self.cursor_pos += delta.map(|x| x as f32);
                 self.cursor_pos.x = self.cursor_pos.x.clamp(0.0, self.framebuffer_size.x);
                 self.cursor_pos.y = self.cursor_pos.y.clamp(0.0, self.framebuffer_size.y);
-                self.touch_move(None, self.cursor_pos);
+                self.screen_input(TouchKind::Move, None, self.cursor_pos);
             }
             geng::Event::CursorMove { position } => {
                 self.cursor_pos = position.map(|x| x as f32);
-                self.touch_move(None, self.cursor_pos);
+                self.screen_input(TouchKind::Move, None, self.cursor_pos);
             }
             geng::Event::MouseRelease { .. } => {
                 if let Some(position) = self.geng.window().cursor_position() {
                     if !self.geng.window().cursor_locked() {
                         self.cursor_pos = position.map(|x| x as f32);
                     }
-                    self.touch_end(None, self.cursor_pos);
+                    self.screen_input(TouchKind::End, None, self.cursor_pos);
                 }
             }
             geng::Event::KeyPress { key: geng::Key::R } => {
@@ -272,31 +304,71 @@ impl geng::State for Game {
             } => {
                 self.transition = Some(geng::state::Transition::Pop);
             }
+            geng::Event::KeyPress {
+                key: geng::Key::Space,
+            } if self.simulation.diff.players() > 1 => {
+                self.keyboard_input(TouchKind::Start);
+            }
+            geng::Event::KeyRelease {
+                key: geng::Key::Space,
+            } if self.simulation.diff.players() > 1 => {
+                self.keyboard_input(TouchKind::End);
+            }
+            // Keys drive the second player, so they do not click
+            geng::Event::KeyPress { .. } | geng::Event::KeyRelease { .. }
+                if self.simulation.diff.players() > 1 => {}
             geng::Event::KeyPress { .. } => {
                 if let Some(cursor_position) = self.geng.window().cursor_position() {
-                    self.touch_start(None, cursor_position.map(|x| x as f32));
+                    self.screen_input(TouchKind::Start, None, cursor_position.map(|x| x as f32));
                 }
             }
             geng::Event::KeyRelease { .. } => {
                 if let Some(cursor_position) = self.geng.window().cursor_position() {
-                    self.touch_end(None, cursor_position.map(|x| x as f32));
+                    self.screen_input(TouchKind::End, None, cursor_position.map(|x| x as f32));
                 }
             }
             geng::Event::TouchStart(touch) => {
-                self.touch_start(Some(touch.id), touch.position.map(|x| x as f32));
+                self.screen_input(
+                    TouchKind::Start,
+                    Some(touch.id),
+                    touch.position.map(|x| x as f32),
+                );
             }
             geng::Event::TouchMove(touch) => {
-                self.touch_move(Some(touch.id), touch.position.map(|x| x as f32));
+                self.screen_input(
+                    TouchKind::Move,
+                    Some(touch.id),
+                    touch.position.map(|x| x as f32),
+                );
             }
             geng::Event::TouchEnd(touch) => {
-                self.touch_end(Some(touch.id), touch.position.map(|x| x as f32));
+                self.screen_input(
+                    TouchKind::End,
+                    Some(touch.id),
+                    touch.position.map(|x| x as f32),
+                );
             }
             _ => {}
         }
     }
     fn update(&mut self, delta_time: f64) {
         self.geng.window().set_cursor_type(geng::CursorType::None);
-        self.update_impl(delta_time as f32);
+        if self.simulation.diff.players() > 1 {
+            self.move_keyboard_cursor(delta_time as f32);
+        }
+        self.fixed_update(delta_time as f32, |_| {});
+        if let Some(race) = &mut self.race {
+            race.update(delta_time as f32);
+            if !self.simulation.game_over {
+                let score = self.simulation.score;
+                race.report(
+                    &self.config,
+                    delta_time as f32,
+                    score,
+                    self.simulation.lives,
+                );
+            }
+        }
     }
     fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
         self.framebuffer_size = framebuffer.size().map(|x| x as f32);
@@ -8,14 +8,14 @@ pub struct Particle {
 }
 
 impl Game {
-    pub fn spawn_particles(&self, pos: vec3<f32>, color: Rgba<f32>) -> Vec<Particle> {
+    pub fn spawn_particles(&mut self, pos: vec3<f32>, color: Rgba<f32>) -> Vec<Particle> {
         (0..self.config.particle_count)
             .map(|_| Particle {
                 pos,
                 vel: vec3(
-                    thread_rng().gen_range(-1.0..1.0),
-                    thread_rng().gen_range(-1.0..1.0),
-                    thread_rng().gen_range(-1.0..1.0),
+                    self.rng.gen_range(-1.0..1.0),
+                    self.rng.gen_range(-1.0..1.0),
+                    self.rng.gen_range(-1.0..1.0),
                 ) * self.config.particle_speed,
                 t: 0.0,
                 color,
@@ -34,7 +34,7 @@ impl Game {
         self.particles_3d.retain(|p| p.t < 1.0);
         self.particles_ui.retain(|p| p.t < 1.0);
     }
-    pub fn draw_particles(&mut self, framebuffer: &mut ugli::Framebuffer) {
+    pub fn draw_particles(&self, framebuffer: &mut ugli::Framebuffer) {
         for p in &self.particles_3d {
             self.draw3d.draw_sprite(
                 framebuffer,
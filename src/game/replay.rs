@@ -0,0 +1,134 @@
+use super::*;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayEvent {
+    /// Number of simulation steps done before this input happened
+    pub tick: u64,
+    pub input: TouchInput,
+}
+
+/// Everything needed to reproduce a run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub diff: Difficulty,
+    pub profile: Profile,
+    /// Second player in co-op or versus
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partner: Option<Profile>,
+    pub simulation_fps: f32,
+    pub events: Vec<ReplayEvent>,
+    /// Set once the run is over, used to pick the best ghost
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<f32>,
+}
+
+impl Replay {
+    pub fn record(&mut self, tick: u64, input: TouchInput) {
+        self.events.push(ReplayEvent { tick, input });
+    }
+
+    pub fn save(&self, path: &std::path::Path) {
+        let result = serde_json::to_string(self)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(path, json)?));
+        match result {
+            Ok(()) => log::info!("Replay saved to {path:?}"),
+            Err(e) => log::error!("Failed to save replay to {path:?}: {e}"),
+        }
+    }
+}
+
+/// Plays a recorded run back by feeding its inputs into a fresh game
+pub struct ReplayState {
+    geng: Geng,
+    assets: Rc<Assets>,
+    config: Rc<Config>,
+    options: Rc<Options>,
+    game: Game,
+    events: Vec<ReplayEvent>,
+    next_event: usize,
+    transition: Option<geng::state::Transition>,
+}
+
+impl ReplayState {
+    pub fn new(
+        geng: &Geng,
+        assets: &Rc<Assets>,
+        config: &Rc<Config>,
+        options: &Rc<Options>,
+        replay: Replay,
+    ) -> Self {
+        if replay.simulation_fps != config.simulation_fps {
+            log::warn!(
+                "Replay was recorded with simulation_fps = {}, it will not play back exactly",
+                replay.simulation_fps,
+            );
+        }
+        let game = Game::new(
+            geng,
+            assets,
+            config,
+            options,
+            replay.diff,
+            std::iter::once(replay.profile)
+                .chain(replay.partner)
+                .collect(),
+            replay.seed,
+            RunMode::Watched,
+        );
+        Self {
+            geng: geng.clone(),
+            assets: assets.clone(),
+            config: config.clone(),
+            options: options.clone(),
+            game,
+            events: replay.events,
+            next_event: 0,
+            transition: None,
+        }
+    }
+
+    fn exit(&mut self) {
+        self.transition = Some(geng::state::Transition::Switch(Box::new(MainMenu::new(
+            &self.geng,
+            &self.assets,
+            &self.config,
+            &self.options,
+        ))));
+    }
+}
+
+impl geng::State for ReplayState {
+    fn handle_event(&mut self, event: geng::Event) {
+        if let geng::Event::KeyPress {
+            key: geng::Key::Escape | geng::Key::Backspace | geng::Key::Enter,
+        } = event
+        {
+            self.exit();
+        }
+    }
+    fn update(&mut self, delta_time: f64) {
+        let events = &self.events;
+        let next_event = &mut self.next_event;
+        self.game.fixed_update(delta_time as f32, |game| {
+            while let Some(event) = events.get(*next_event) {
+                if event.tick > game.simulation.tick {
+                    break;
+                }
+                game.input(event.input.clone());
+                *next_event += 1;
+            }
+        });
+        if self.game.transition.take().is_some() {
+            // The run is over, but a replay should not go to the final screen
+            self.exit();
+        }
+    }
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        geng::State::draw(&mut self.game, framebuffer);
+    }
+    fn transition(&mut self) -> Option<geng::state::Transition> {
+        self.transition.take()
+    }
+}
@@ -1,24 +1,187 @@
-use crate::final_screen::FinalScreen;
+use crate::final_screen::{FinalScreen, FinishedRun};
+use crate::highscores::{self, HighScore};
+use crate::versus_screen::{VersusPlayer, VersusScreen};
 
 use super::*;
 
 impl Game {
-    pub fn update_impl(&mut self, delta_time: f32) {
-        self.real_time += delta_time;
+    /// Advances the simulation in fixed steps, leftover time is carried over to the next frame.
+    /// `before_step` is called before every step, which is where replays feed their inputs
+    pub fn fixed_update(&mut self, delta_time: f32, mut before_step: impl FnMut(&mut Self)) {
+        let fixed_delta_time = 1.0 / self.config.simulation_fps;
+        self.accumulated_time += delta_time;
+        let mut steps = 0;
+        while self.accumulated_time >= fixed_delta_time {
+            if steps == self.config.max_simulation_steps {
+                // Can not keep up, slow down instead of freezing completely
+                self.accumulated_time = 0.0;
+                break;
+            }
+            before_step(self);
+            self.simulation.update(fixed_delta_time);
+            if let Some(ghost) = &mut self.ghost {
+                ghost.update(fixed_delta_time);
+            }
+            self.handle_simulation_events();
+            self.update_impl(fixed_delta_time);
+            self.accumulated_time -= fixed_delta_time;
+            steps += 1;
+        }
+    }
 
-        self.last_score_t += delta_time;
+    /// How far between the last two simulation steps are we, used for rendering
+    pub fn interpolation_alpha(&self) -> f32 {
+        (self.accumulated_time * self.config.simulation_fps).clamp(0.0, 1.0)
+    }
 
-        if self.time_left < 0.0 || self.lives == 0 {
-            if self.end_timer == 0.0 && self.lives != 0 {
-                self.assets.sfx.timer.play();
+    fn handle_simulation_events(&mut self) {
+        let events = self.simulation.take_events();
+        if let Some(achievements) = &self.achievements {
+            let mut achievements = achievements.borrow_mut();
+            achievements.track(&self.simulation, &events);
+            let unlocked = achievements.unlock(&self.config.achievements);
+            if !unlocked.is_empty() {
+                achievements.save();
+                self.toasts
+                    .extend(unlocked.iter().map(|achievement| achievement.name.clone()));
             }
-            if self.end_timer == 0.0 {
-                while let Some(touch) = self.touches.pop() {
-                    self.touch_end(touch.id, touch.position);
+        }
+        for event in events {
+            match event {
+                simulation::Event::Picked => self.assets.sfx.pick.play_random_pitch(),
+                simulation::Event::PickFailed => self.assets.sfx.error.play_random_pitch(),
+                simulation::Event::Juggled => self.assets.sfx.juggle.play_random_pitch(),
+                simulation::Event::Thrown => self.assets.sfx.throw.play_random_pitch(),
+                simulation::Event::Delivered {
+                    pos,
+                    score,
+                    color_match,
+                    streak,
+                    pair,
+                    perfect,
+                    player,
+                } => {
+                    if color_match == Some(false) {
+                        self.assets.sfx.error.play_random_pitch();
+                    } else {
+                        self.assets.sfx.score.play_random_pitch();
+                    }
+                    let particles = self.spawn_particles(pos, self.config.score_color);
+                    self.particles_3d.extend(particles);
+                    self.last_score_t = 0.0;
+                    self.last_score_player = player;
+                    self.last_score_text = format!("{:+}", score.floor() as i32);
+                    let mut combo = Vec::new();
+                    if streak > 1 {
+                        let multiplier = self.config.combo.streak_multiplier(streak);
+                        combo.push(format!("combo {streak} x{multiplier}"));
+                    }
+                    if pair {
+                        combo.push("pair".to_owned());
+                    }
+                    if perfect {
+                        combo.push("perfect".to_owned());
+                    }
+                    self.last_combo_text = combo.join(" ");
+                    if let Some(race) = &mut self.race {
+                        race.delivered(score);
+                    }
+                }
+                simulation::Event::Knocked { pos } => {
+                    self.assets.sfx.error.play_random_pitch();
+                    let particles =
+                        self.spawn_particles(pos.extend(0.0), self.config.explosion_color);
+                    self.particles_ui.extend(particles);
+                }
+                simulation::Event::Dropped { pos } => {
+                    let particles =
+                        self.spawn_particles(pos.extend(0.0), self.config.explosion_color);
+                    self.particles_ui.extend(particles);
+                }
+                simulation::Event::Missed { pos } | simulation::Event::Rejected { pos } => {
+                    let particles = self.spawn_particles(pos, self.config.explosion_color);
+                    self.particles_3d.extend(particles);
+                }
+                simulation::Event::PowerUpHit { pos, .. } => {
+                    self.assets.sfx.score.play_random_pitch();
+                    let particles = self.spawn_particles(pos, self.config.power_up_color);
+                    self.particles_3d.extend(particles);
+                }
+                simulation::Event::HazardHit { kind } => {
+                    let hazard = &self.config.hazards[kind];
+                    if hazard.knock != 0.0 {
+                        self.assets.sfx.error.play_random_pitch();
+                    }
+                    // Stronger shake takes over, weaker one does not cut the current one short
+                    let current = self.camera_shake * (1.0 - self.camera_shake_t);
+                    if hazard.shake > current {
+                        self.camera_shake = hazard.shake;
+                        self.camera_shake_t = 0.0;
+                    }
+                }
+                simulation::Event::LifeSpared => self.assets.sfx.explosion.play_random_pitch(),
+                simulation::Event::LifeLost { lives_left } => {
+                    self.assets.sfx.explosion.play_random_pitch();
+                    if lives_left == 0 {
+                        self.lose_sfx = Some(self.assets.sfx.lose.play());
+                        self.music.stop();
+                    }
+                }
+                simulation::Event::GameOver { reason } => {
+                    if reason == simulation::GameOverReason::TimeUp {
+                        self.assets.sfx.timer.play();
+                    }
+                    self.recording.score = Some(self.simulation.score);
+                    self.save_replay();
+                    self.finish_daily();
+                    if let Some(race) = &mut self.race {
+                        race.finished(self.simulation.score);
+                    }
                 }
             }
+        }
+    }
+
+    pub fn update_impl(&mut self, delta_time: f32) {
+        self.real_time += delta_time;
+
+        self.last_score_t += delta_time;
+        if !self.toasts.is_empty() {
+            self.toast_t += delta_time / self.config.achievement_toast_time;
+            if self.toast_t > 1.0 {
+                self.toasts.pop_front();
+                self.toast_t = 0.0;
+            }
+        }
+        self.camera_shake_t =
+            (self.camera_shake_t + delta_time / self.config.camera_shake_time).min(1.0);
+
+        if self.simulation.game_over {
             self.end_timer += delta_time / 3.0;
-            if self.end_timer > 1.0 {
+            if self.end_timer > 1.0 && self.simulation.diff.versus {
+                let players = self
+                    .profiles
+                    .iter()
+                    .zip(&self.simulation.players)
+                    .map(|(profile, player)| VersusPlayer {
+                        profile: profile.clone(),
+                        score: player.score,
+                        lives: player.lives,
+                        stats: player.stats.clone(),
+                    })
+                    .collect();
+                self.transition = Some(geng::state::Transition::Switch(Box::new(
+                    VersusScreen::new(
+                        &self.geng,
+                        &self.assets,
+                        &self.config,
+                        &self.options,
+                        self.simulation.diff.clone(),
+                        players,
+                        self.simulation.winner(),
+                    ),
+                )));
+            } else if self.end_timer > 1.0 {
                 self.transition = Some(geng::state::Transition::Switch(Box::new(
                     geng::LoadingScreen::new(
                         &self.geng,
@@ -27,24 +190,31 @@ impl Game {
                             let geng = self.geng.clone();
                             let assets = self.assets.clone();
                             let config = self.config.clone();
-                            let diff = self.diff.clone();
-                            let score = self.score;
-                            let name = self.name.clone();
+                            let options = self.options.clone();
+                            let run = FinishedRun {
+                                diff: self.simulation.diff.clone(),
+                                score: self.simulation.score,
+                                profiles: self.profiles.clone(),
+                                player_scores: self
+                                    .simulation
+                                    .players
+                                    .iter()
+                                    .map(|player| player.score)
+                                    .collect(),
+                            };
+                            let recording = self.recording.clone();
                             async move {
-                                #[cfg(feature = "leaderboard")]
-                                let pos_and_scores =
-                                    Some(leaderboard::submit(diff.clone(), &name, score).await);
-                                #[cfg(not(feature = "leaderboard"))]
-                                let pos_and_scores = None;
-                                FinalScreen::new(
-                                    &geng,
-                                    &assets,
-                                    &config,
-                                    diff,
-                                    score,
-                                    name,
-                                    pos_and_scores,
-                                )
+                                // Only runs when the final screen is shown, so not for replays
+                                let local_place = highscores::record(HighScore {
+                                    name: run.profiles[0].name.clone(),
+                                    score: run.score,
+                                    date: Date::today(),
+                                    seed: recording.seed,
+                                    diff: run.diff.clone(),
+                                });
+                                ghosts::save_if_best(&options, &recording);
+                                FinalScreen::submit(geng, assets, config, options, run, local_place)
+                                    .await
                             }
                         },
                     ),
@@ -52,190 +222,6 @@ impl Game {
             }
         }
 
-        self.add_raw_score(delta_time * self.config.juggling_score_multiplier);
-        self.time_left -= delta_time;
-
-        for touch in &mut self.touches {
-            if let Some(remove_time) = &mut touch.remove_time {
-                *remove_time =
-                    (*remove_time + delta_time / self.config.throw_animation_time).min(1.0);
-            }
-            touch.throw_animation_time = (touch.throw_animation_time
-                + delta_time / self.config.throw_animation_time)
-                .min(1.0);
-            if touch.holding.is_some() {
-                touch.throw_animation_time = 0.0;
-            }
-            touch.error_animation_time = (touch.error_animation_time
-                + delta_time / self.config.error_animation_time)
-                .min(1.0);
-            if touch.holding.is_some() {
-                touch.error_animation_time = 1.0;
-            }
-        }
-        self.touches.retain(|touch| {
-            touch.id == None
-                || match touch.remove_time {
-                    Some(time) => time < 1.0,
-                    None => true,
-                }
-        });
-
         self.update_particles(delta_time);
-
-        let delta_time = delta_time * self.diff.time_scale;
-
-        self.update_juggling_items(delta_time);
-        self.my_latitude += self.config.ride_speed * delta_time; // Move forward
-        self.update_mailboxes();
-        self.update_houses();
-        self.update_thrown_items(delta_time);
-    }
-
-    fn add_raw_score(&mut self, raw_score: f32) {
-        if self.end_timer != 0.0 {
-            return;
-        }
-        let multiplier = self.juggling_items.len()
-            + 1
-            + self
-                .touches
-                .iter()
-                .filter(|touch| touch.holding.is_some())
-                .count();
-        let scored = raw_score * multiplier as f32;
-        self.score += scored;
-        if raw_score == self.config.deliver_score {
-            self.last_score_t = 0.0;
-            self.last_score_text = format!("+{}", scored.floor() as i32);
-        }
-    }
-
-    fn update_juggling_items(&mut self, delta_time: f32) {
-        for item in &mut self.juggling_items {
-            item.vel.y -= self.config.gravity * delta_time;
-            item.pos += item.vel * delta_time;
-            item.rot += item.w * delta_time;
-        }
-        let mut lives_lost = 0;
-        let mut spawn_particles = None;
-        self.juggling_items.retain(|item| {
-            if item.pos.y > self.bag_position.min.y {
-                true
-            } else {
-                lives_lost += 1;
-                spawn_particles = Some((item.pos, self.config.explosion_color));
-                false
-            }
-        });
-        if let Some((pos, color)) = spawn_particles {
-            self.particles_ui
-                .extend(self.spawn_particles(pos.extend(0.0), color));
-        }
-        for _ in 0..lives_lost {
-            self.lose_life();
-        }
-    }
-
-    fn update_houses(&mut self) {
-        self.houses.retain(|house| {
-            house.latitude > self.my_latitude - self.config.despawn_distance.to_radians()
-        });
-        while self.houses.last().map_or(true, |house| {
-            house.latitude < self.my_latitude + self.config.spawn_distance.to_radians()
-        }) {
-            let last_latitude = self
-                .houses
-                .last()
-                .map_or(self.my_latitude, |house| house.latitude);
-            for x in [-1, 1] {
-                self.houses.push(House {
-                    x: x as f32 * (self.config.road_width + self.config.house_offset),
-                    latitude: last_latitude + self.config.distance_between_houses.to_radians(),
-                    texture: thread_rng().gen_range(0..self.assets.houses.len()),
-                });
-            }
-        }
-    }
-
-    fn update_mailboxes(&mut self) {
-        self.mailboxes.retain(|mailbox| {
-            mailbox.latitude > self.my_latitude - self.config.despawn_distance.to_radians()
-        });
-        while self.mailboxes.last().map_or(true, |mailbox| {
-            mailbox.latitude < self.my_latitude + self.config.spawn_distance.to_radians()
-        }) {
-            let last_latitude = self
-                .mailboxes
-                .last()
-                .map_or(self.my_latitude, |mailbox| mailbox.latitude);
-            let (left, right) = if thread_rng().gen_bool(self.config.double_mailbox_probability) {
-                (true, true)
-            } else if thread_rng().gen() {
-                (true, false)
-            } else {
-                (false, true)
-            };
-            for (x, spawn) in itertools::izip![[-1, 1], [left, right]] {
-                if !spawn {
-                    continue;
-                }
-                self.mailboxes.push(Mailbox {
-                    id: self.next_id,
-                    x: x as f32 * (self.config.road_width + self.config.mailbox_size / 2.0),
-                    latitude: last_latitude + self.config.distance_between_mailboxes.to_radians(),
-                    color: thread_rng().gen_range(0..self.config.mailbox_colors.len()),
-                });
-                self.next_id += 1;
-            }
-        }
-    }
-
-    fn update_thrown_items(&mut self, delta_time: f32) {
-        for item in &mut self.thrown_items {
-            item.t += delta_time;
-            item.item.rot += item.item.w * delta_time;
-        }
-        let mut raw_score_added = 0.0;
-        let mut lives_lost = 0;
-        let mut spawn_particles = None;
-        self.thrown_items.retain(|item| {
-            if item.t < self.config.throw_time {
-                true
-            } else {
-                let index = self
-                    .mailboxes
-                    .iter()
-                    .position(|mailbox| mailbox.id == item.to_id);
-                if let Some(index) = index {
-                    raw_score_added += self.config.deliver_score;
-                    self.assets.sfx.score.play_random_pitch();
-                    self.mailboxes.remove(index);
-                    spawn_particles = Some((item.to, self.config.score_color));
-                } else {
-                    spawn_particles = Some((item.to, self.config.explosion_color));
-                    lives_lost += 1;
-                }
-                false
-            }
-        });
-        if let Some((pos, color)) = spawn_particles {
-            self.particles_3d.extend(self.spawn_particles(pos, color));
-        }
-        for _ in 0..lives_lost {
-            self.lose_life();
-        }
-        self.add_raw_score(raw_score_added);
-    }
-
-    fn lose_life(&mut self) {
-        if self.lives != 0 {
-            self.lives -= 1;
-            self.assets.sfx.explosion.play_random_pitch();
-            if self.lives == 0 {
-                self.lose_sfx = Some(self.assets.sfx.lose.play());
-                self.music.stop();
-            }
-        }
     }
 }
@@ -0,0 +1,124 @@
+//! Personal best runs kept as replay files, played back as ghosts on the same seed and difficulty.
+//! Only seeds that come up again get ghosts: the one fixed with `--seed` and the daily challenge's
+
+use super::*;
+
+/// Least recently saved ghosts are removed past this many
+const MAX_GHOSTS: usize = 50;
+
+fn dir() -> Option<std::path::PathBuf> {
+    #[cfg(target_arch = "wasm32")]
+    return None;
+    #[cfg(not(target_arch = "wasm32"))]
+    directories::ProjectDirs::from("", "", "juggle-mail").map(|dirs| dirs.data_dir().join("ghosts"))
+}
+
+/// Whether a run on this seed can be played again
+fn repeatable(options: &Options, seed: u64, diff: &Difficulty) -> bool {
+    options.seed == Some(seed) || diff.daily.map(daily::seed) == Some(seed)
+}
+
+/// Same for every launch, unlike the std hasher
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Every seed and difficulty has a single file, overwritten when the score is beaten
+fn file_name(seed: u64, diff: &Difficulty) -> String {
+    let diff = serde_json::to_string(diff).unwrap();
+    format!("{seed}-{:016x}.json", fnv1a(diff.as_bytes()))
+}
+
+fn read(path: &std::path::Path, seed: u64, diff: &Difficulty) -> Option<Replay> {
+    let replay: Replay = serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+    // In case of a hash collision
+    (replay.seed == seed && replay.diff == *diff).then_some(replay)
+}
+
+pub fn load(options: &Options, seed: u64, diff: &Difficulty) -> Option<Replay> {
+    if !repeatable(options, seed, diff) {
+        return None;
+    }
+    read(&dir()?.join(file_name(seed, diff)), seed, diff)
+}
+
+/// Keeps the finished run if it beat the saved one of the same seed and difficulty
+pub fn save_if_best(options: &Options, replay: &Replay) {
+    let (Some(dir), Some(score)) = (dir(), replay.score) else {
+        return;
+    };
+    if !repeatable(options, replay.seed, &replay.diff) {
+        return;
+    }
+    let path = dir.join(file_name(replay.seed, &replay.diff));
+    if let Some(best) = read(&path, replay.seed, &replay.diff) {
+        if best.score.unwrap_or(0.0) >= score {
+            return;
+        }
+    }
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        log::error!("Failed to create {dir:?}: {e}");
+        return;
+    }
+    replay.save(&path);
+    prune(&dir);
+}
+
+/// Removes the least recently saved ghosts past [MAX_GHOSTS]
+fn prune(dir: &std::path::Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let mut files = entries
+        .flatten()
+        .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+        .collect::<Vec<_>>();
+    if files.len() <= MAX_GHOSTS {
+        return;
+    }
+    files.sort();
+    for (_modified, path) in &files[..files.len() - MAX_GHOSTS] {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::error!("Failed to remove {path:?}: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::*;
+
+    #[test]
+    fn every_seed_and_difficulty_has_its_own_file() {
+        let harder = Difficulty { lives: 1, ..diff() };
+        assert_eq!(file_name(1, &diff()), file_name(1, &diff()));
+        assert_ne!(file_name(1, &diff()), file_name(2, &diff()));
+        assert_ne!(file_name(1, &diff()), file_name(1, &harder));
+    }
+
+    #[test]
+    fn only_fixed_and_daily_seeds_get_ghosts() {
+        let date = Date {
+            year: 2024,
+            month: 5,
+            day: 17,
+        };
+        let daily = Difficulty {
+            daily: Some(date),
+            ..diff()
+        };
+        let fixed = Options {
+            seed: Some(1),
+            ..default()
+        };
+        assert!(!repeatable(&Options::default(), 1, &diff()));
+        assert!(repeatable(&fixed, 1, &diff()));
+        assert!(!repeatable(&fixed, 2, &diff()));
+        assert!(repeatable(&Options::default(), daily::seed(date), &daily));
+        assert!(!repeatable(&Options::default(), 1, &daily));
+    }
+}
@@ -0,0 +1,123 @@
+//! High scores kept on this device, used when the online leaderboard is not available
+
+use super::*;
+
+use leaderboard::LeaderboardEntry;
+
+/// How many scores are kept for every difficulty
+const SCORES_PER_DIFFICULTY: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    pub score: f32,
+    pub date: Date,
+    pub seed: u64,
+    pub diff: Difficulty,
+}
+
+impl From<HighScore> for LeaderboardEntry {
+    fn from(high_score: HighScore) -> Self {
+        Self {
+            diff: high_score.diff,
+            name: high_score.name,
+            score: high_score.score,
+        }
+    }
+}
+
+/// Scores of every difficulty, best first
+pub fn load() -> Vec<HighScore> {
+    preferences::load("highscores").unwrap_or_default()
+}
+
+/// Saves the score and returns its place among the scores of the same difficulty
+pub fn record(high_score: HighScore) -> usize {
+    let mut high_scores = load();
+    let place = insert(&mut high_scores, high_score);
+    preferences::save("highscores", &high_scores);
+    place
+}
+
+/// Adds the score keeping only the best ones of every difficulty, returns its place
+fn insert(high_scores: &mut Vec<HighScore>, high_score: HighScore) -> usize {
+    let place = high_scores
+        .iter()
+        .filter(|other| other.diff == high_score.diff && other.score >= high_score.score)
+        .count();
+    high_scores.push(high_score);
+    // Stable sort, so older scores stay above equal new ones
+    high_scores.sort_by_key(|high_score| -r32(high_score.score));
+    let mut kept: Vec<HighScore> = Vec::new();
+    for high_score in high_scores.drain(..) {
+        if kept
+            .iter()
+            .filter(|other| other.diff == high_score.diff)
+            .count()
+            < SCORES_PER_DIFFICULTY
+        {
+            kept.push(high_score);
+        }
+    }
+    *high_scores = kept;
+    place
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::*;
+
+    fn high_score(name: &str, score: f32, lives: usize) -> HighScore {
+        HighScore {
+            name: name.to_owned(),
+            score,
+            date: Date {
+                year: 2024,
+                month: 1,
+                day: 1,
+            },
+            seed: 0,
+            diff: Difficulty { lives, ..diff() },
+        }
+    }
+
+    #[test]
+    fn place_counts_better_and_equal_scores_of_the_same_difficulty() {
+        let mut high_scores = Vec::new();
+        assert_eq!(insert(&mut high_scores, high_score("a", 100.0, 3)), 0);
+        assert_eq!(insert(&mut high_scores, high_score("b", 300.0, 3)), 0);
+        assert_eq!(insert(&mut high_scores, high_score("c", 200.0, 3)), 1);
+        // Ties go below the older score
+        assert_eq!(insert(&mut high_scores, high_score("d", 200.0, 3)), 2);
+        // Other difficulties do not count
+        assert_eq!(insert(&mut high_scores, high_score("e", 50.0, 1)), 0);
+        let names: Vec<&str> = high_scores.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, ["b", "c", "d", "a", "e"]);
+    }
+
+    #[test]
+    fn only_the_best_scores_of_every_difficulty_are_kept() {
+        let mut high_scores = Vec::new();
+        for score in 0..SCORES_PER_DIFFICULTY + 5 {
+            insert(&mut high_scores, high_score("a", score as f32, 3));
+        }
+        insert(&mut high_scores, high_score("b", 0.0, 1));
+        let kept: Vec<f32> = high_scores
+            .iter()
+            .filter(|h| h.diff.lives == 3)
+            .map(|h| h.score)
+            .collect();
+        assert_eq!(kept.len(), SCORES_PER_DIFFICULTY);
+        assert_eq!(kept[0], (SCORES_PER_DIFFICULTY + 4) as f32);
+        assert_eq!(*kept.last().unwrap(), 5.0);
+        // A full difficulty does not push out scores of another one
+        assert!(high_scores.iter().any(|h| h.name == "b"));
+
+        // Too low to be kept, but still gets a place
+        let place = insert(&mut high_scores, high_score("c", 1.0, 3));
+        assert_eq!(place, SCORES_PER_DIFFICULTY);
+        assert!(!high_scores.iter().any(|h| h.name == "c"));
+    }
+}
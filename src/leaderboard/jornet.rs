@@ -0,0 +1,85 @@
+use super::*;
+
+pub struct JornetBackend {
+    /// Official server if not specified
+    host: Option<String>,
+    id: String,
+    key: String,
+}
+
+impl JornetBackend {
+    pub fn new(host: Option<String>, id: String, key: String) -> Self {
+        Self { host, id, key }
+    }
+
+    fn leaderboard(&self) -> anyhow::Result<::jornet::Leaderboard> {
+        Ok(::jornet::Leaderboard::with_host_and_leaderboard(
+            self.host.clone(),
+            self.id.parse()?,
+            self.key.parse()?,
+        ))
+    }
+
+    fn as_player(&self, player: &Player) -> anyhow::Result<::jornet::Leaderboard> {
+        let mut leaderboard = self.leaderboard()?;
+        leaderboard.as_player(serde_json::from_value(player.credentials.clone())?);
+        Ok(leaderboard)
+    }
+}
+
+impl LeaderboardBackend for JornetBackend {
+    fn id(&self) -> &str {
+        "jornet"
+    }
+
+    fn register_player(&self, name: &str) -> future::LocalBoxFuture<'_, anyhow::Result<Player>> {
+        let name = name.to_owned();
+        async move {
+            let mut leaderboard = self.leaderboard()?;
+            let player = leaderboard.create_player(Some(&name)).await?;
+            Ok(Player {
+                name,
+                credentials: serde_json::to_value(player)?,
+            })
+        }
+        .boxed_local()
+    }
+
+    fn submit_score(
+        &self,
+        player: &Player,
+        score: f32,
+        meta: &str,
+    ) -> future::LocalBoxFuture<'_, anyhow::Result<()>> {
+        let leaderboard = self.as_player(player);
+        let meta = meta.to_owned();
+        async move {
+            leaderboard?.send_score_with_meta(score, &meta).await?;
+            Ok(())
+        }
+        .boxed_local()
+    }
+
+    fn fetch_scores(&self) -> future::LocalBoxFuture<'_, anyhow::Result<Vec<Score>>> {
+        async move {
+            let scores = self.leaderboard()?.get_leaderboard().await?;
+            Ok(scores
+                .into_iter()
+                .map(|score| Score {
+                    player: score.player,
+                    score: score.score,
+                    meta: score.meta,
+                })
+                .collect())
+        }
+        .boxed_local()
+    }
+
+    fn legacy_player(&self) -> Option<Player> {
+        let player: ::jornet::Player = preferences::load("player")?;
+        Some(Player {
+            name: player.name.clone(),
+            credentials: serde_json::to_value(player).ok()?,
+        })
+    }
+}
@@ -0,0 +1,235 @@
+use super::*;
+
+mod file;
+#[cfg(feature = "leaderboard")]
+mod jornet;
+mod mock;
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "leaderboard")]
+pub use self::jornet::JornetBackend;
+pub use file::FileBackend;
+pub use mock::MockBackend;
+
+/// A registered player, credentials are specific to the backend that created it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Player {
+    pub name: String,
+    pub credentials: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Score {
+    pub player: String,
+    pub score: f32,
+    pub meta: Option<String>,
+}
+
+/// A single row, either from the leaderboard or from local high scores
+#[derive(Debug, Clone)]
+pub struct LeaderboardEntry {
+    pub diff: Difficulty,
+    pub name: String,
+    pub score: f32,
+}
+
+/// Best entry of every player for the difficulty, best first
+pub fn standings(entries: &[LeaderboardEntry], diff: &Difficulty) -> Vec<LeaderboardEntry> {
+    let mut entries: Vec<LeaderboardEntry> = entries
+        .iter()
+        .filter(|entry| entry.diff == *diff)
+        .cloned()
+        .collect();
+    entries.sort_by_key(|entry| -r32(entry.score));
+    let mut names_seen = HashSet::new();
+    entries.retain(|entry| names_seen.insert(entry.name.clone()));
+    entries
+}
+
+/// Somewhere to submit scores to and fetch them from
+pub trait LeaderboardBackend {
+    /// Used to keep players registered with different backends apart
+    fn id(&self) -> &str;
+    fn register_player(&self, name: &str) -> future::LocalBoxFuture<'_, anyhow::Result<Player>>;
+    fn submit_score(
+        &self,
+        player: &Player,
+        score: f32,
+        meta: &str,
+    ) -> future::LocalBoxFuture<'_, anyhow::Result<()>>;
+    /// All the scores ever submitted, in no particular order
+    fn fetch_scores(&self) -> future::LocalBoxFuture<'_, anyhow::Result<Vec<Score>>>;
+    /// Player saved by versions of the game that only had this backend
+    fn legacy_player(&self) -> Option<Player> {
+        None
+    }
+}
+
+/// Returns [None] if the configured backend is not compiled in
+pub fn create_backend(config: &LeaderboardConfig) -> Option<Rc<dyn LeaderboardBackend>> {
+    match config.backend {
+        #[cfg(feature = "leaderboard")]
+        LeaderboardBackendKind::Jornet => Some(Rc::new(JornetBackend::new(
+            config.host.clone(),
+            config.id.clone(),
+            config.key.clone(),
+        ))),
+        #[cfg(not(feature = "leaderboard"))]
+        LeaderboardBackendKind::Jornet => {
+            log::info!("Built without the leaderboard feature, only keeping scores locally");
+            None
+        }
+        LeaderboardBackendKind::File => Some(Rc::new(FileBackend::new(config.path.clone()))),
+        LeaderboardBackendKind::Mock => Some(Rc::new(MockBackend::default())),
+    }
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    RegisterPlayer(anyhow::Error),
+    SendScore(anyhow::Error),
+    /// Score got sent, but the leaderboard could not be shown
+    FetchScores(anyhow::Error),
+}
+
+impl SubmitError {
+    /// If not, the score is queued to be sent again
+    pub fn score_was_sent(&self) -> bool {
+        matches!(self, Self::FetchScores(_))
+    }
+}
+
+impl std::fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RegisterPlayer(e) => write!(f, "Failed to register player: {e:#}"),
+            Self::SendScore(e) => write!(f, "Failed to send score: {e:#}"),
+            Self::FetchScores(e) => write!(f, "Failed to fetch scores: {e:#}"),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+/// Score that failed to submit, to be sent again on the next launch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnsentScore {
+    pub diff: Difficulty,
+    pub name: String,
+    pub score: f32,
+}
+
+fn queue_key(backend: &dyn LeaderboardBackend) -> String {
+    format!("{}_unsent_scores", backend.id())
+}
+
+fn load_queue(backend: &dyn LeaderboardBackend) -> Vec<UnsentScore> {
+    preferences::load(&queue_key(backend)).unwrap_or_default()
+}
+
+fn update_queue(backend: &dyn LeaderboardBackend, f: impl FnOnce(&mut Vec<UnsentScore>)) {
+    let mut queue = load_queue(backend);
+    f(&mut queue);
+    preferences::save(&queue_key(backend), &queue);
+}
+
+async fn send(backend: &dyn LeaderboardBackend, unsent: &UnsentScore) -> Result<(), SubmitError> {
+    let player_key = format!("{}_player", backend.id());
+    let saved = preferences::load::<Player>(&player_key).or_else(|| {
+        let player = backend.legacy_player()?;
+        log::info!("Migrating player saved by an older version");
+        preferences::save(&player_key, &player);
+        Some(player)
+    });
+    let player = match saved {
+        Some(player) if player.name == unsent.name => {
+            log::info!("Returning player");
+            player
+        }
+        player => {
+            if player.is_some() {
+                log::info!("Name has changed");
+            } else {
+                log::info!("New player");
+            }
+            let player = backend
+                .register_player(&unsent.name)
+                .await
+                .map_err(SubmitError::RegisterPlayer)?;
+            preferences::save(&player_key, &player);
+            player
+        }
+    };
+    let meta = serde_json::to_string(&unsent.diff).expect("difficulty is always serializable");
+    backend
+        .submit_score(&player, unsent.score, &meta)
+        .await
+        .map_err(SubmitError::SendScore)
+}
+
+/// Scores of every difficulty
+pub async fn fetch(backend: &dyn LeaderboardBackend) -> anyhow::Result<Vec<LeaderboardEntry>> {
+    let scores = backend.fetch_scores().await?;
+    Ok(scores
+        .into_iter()
+        .filter_map(|score| {
+            // Scores with unknown meta were submitted by some other version of the game
+            let diff = serde_json::from_str(score.meta.as_deref()?).ok()?;
+            Some(LeaderboardEntry {
+                diff,
+                name: score.player,
+                score: score.score,
+            })
+        })
+        .collect())
+}
+
+/// Sends the scores that failed to submit before, stops at the first failure
+pub async fn submit_queued(backend: &dyn LeaderboardBackend) {
+    for unsent in load_queue(backend) {
+        match send(backend, &unsent).await {
+            Ok(()) => {
+                log::info!("Sent queued score {unsent:?}");
+                update_queue(backend, |queue| queue.retain(|other| *other != unsent));
+            }
+            Err(e) => {
+                log::warn!("Queued scores are still not sent: {e}");
+                break;
+            }
+        }
+    }
+}
+
+/// Returns the place of the score, counting players with a better one
+pub async fn submit(
+    backend: &dyn LeaderboardBackend,
+    diff: Difficulty,
+    name: &str,
+    score: f32,
+) -> Result<usize, SubmitError> {
+    let unsent = UnsentScore {
+        diff,
+        name: name.to_owned(),
+        score,
+    };
+    let sent = send(backend, &unsent).await;
+    update_queue(backend, |queue| {
+        queue.retain(|other| *other != unsent);
+        if sent.is_err() {
+            queue.push(unsent.clone());
+        }
+    });
+    sent?;
+
+    let entries = fetch(backend).await.map_err(SubmitError::FetchScores)?;
+    Ok(place(&entries, &unsent.diff, score))
+}
+
+/// Place of the score among the best scores of other players
+pub fn place(entries: &[LeaderboardEntry], diff: &Difficulty, score: f32) -> usize {
+    standings(entries, diff)
+        .iter()
+        .filter(|entry| entry.score > score)
+        .count()
+}
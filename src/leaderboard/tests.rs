@@ -0,0 +1,233 @@
+use super::*;
+
+use crate::testing::*;
+use futures::executor::block_on;
+
+fn difficulty(lives: usize) -> Difficulty {
+    Difficulty { lives, ..diff() }
+}
+
+fn entry(name: &str, score: f32, lives: usize) -> LeaderboardEntry {
+    LeaderboardEntry {
+        diff: difficulty(lives),
+        name: name.to_owned(),
+        score,
+    }
+}
+
+#[test]
+fn standings_keep_the_best_score_of_every_player() {
+    let entries = [
+        entry("a", 100.0, 3),
+        entry("b", 300.0, 3),
+        entry("a", 200.0, 3),
+        entry("c", 500.0, 1),
+    ];
+    let standings: Vec<(String, f32)> = standings(&entries, &difficulty(3))
+        .into_iter()
+        .map(|entry| (entry.name, entry.score))
+        .collect();
+    assert_eq!(
+        standings,
+        [("b".to_owned(), 300.0), ("a".to_owned(), 200.0)]
+    );
+}
+
+#[test]
+fn place_counts_players_with_a_better_score() {
+    let entries = [
+        entry("a", 100.0, 3),
+        entry("b", 300.0, 3),
+        entry("a", 250.0, 3),
+        entry("c", 500.0, 1),
+    ];
+    let diff = difficulty(3);
+    assert_eq!(place(&entries, &diff, 400.0), 0);
+    assert_eq!(place(&entries, &diff, 260.0), 1);
+    // Only the best score of a player counts, and ties do not push the score down
+    assert_eq!(place(&entries, &diff, 250.0), 1);
+    assert_eq!(place(&entries, &diff, 50.0), 2);
+}
+
+#[test]
+fn file_backend_keeps_scores_between_launches() {
+    let path = std::env::temp_dir().join(format!(
+        "juggle-mail-leaderboard-{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let backend = FileBackend::new(path.clone());
+    block_on(async {
+        assert!(backend.fetch_scores().await.unwrap().is_empty());
+        let player = backend.register_player("a").await.unwrap();
+        backend.submit_score(&player, 100.0, "meta").await.unwrap();
+        backend.submit_score(&player, 200.0, "meta").await.unwrap();
+    });
+    let scores = block_on(FileBackend::new(path.clone()).fetch_scores()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let scores: Vec<(String, f32, Option<String>)> = scores
+        .into_iter()
+        .map(|score| (score.player, score.score, score.meta))
+        .collect();
+    assert_eq!(
+        scores,
+        [
+            ("a".to_owned(), 100.0, Some("meta".to_owned())),
+            ("a".to_owned(), 200.0, Some("meta".to_owned())),
+        ]
+    );
+}
+
+#[test]
+fn mock_backend_runs_the_final_screen_flow_offline() {
+    let backend = MockBackend::default();
+    let diff = difficulty(3);
+    let submit_score = |diff: &Difficulty, name: &str, score: f32| {
+        block_on(submit(&backend, diff.clone(), name, score)).unwrap()
+    };
+    assert_eq!(submit_score(&diff, "a", 100.0), 0);
+    assert_eq!(submit_score(&diff, "b", 300.0), 0);
+    assert_eq!(submit_score(&diff, "c", 200.0), 1);
+    assert_eq!(submit_score(&difficulty(1), "d", 50.0), 0);
+
+    let entries = block_on(fetch(&backend)).unwrap();
+    assert_eq!(entries.len(), 4);
+    assert_eq!(standings(&entries, &diff).len(), 3);
+}
+
+/// Works like [MockBackend], but can be made to fail on demand
+struct FlakyBackend {
+    /// Unsent scores are saved by backend id, so every test needs its own
+    id: &'static str,
+    inner: MockBackend,
+    /// Every request fails
+    offline: Cell<bool>,
+    /// Sending this score fails
+    failing_score: Cell<Option<f32>>,
+    /// Fetching scores fails
+    failing_fetch: Cell<bool>,
+}
+
+impl FlakyBackend {
+    fn new(id: &'static str) -> Self {
+        let backend = Self {
+            id,
+            inner: MockBackend::default(),
+            offline: Cell::new(false),
+            failing_score: Cell::new(None),
+            failing_fetch: Cell::new(false),
+        };
+        // Left over from an earlier test run
+        update_queue(&backend, Vec::clear);
+        backend
+    }
+
+    fn queue(&self) -> Vec<f32> {
+        load_queue(self).iter().map(|unsent| unsent.score).collect()
+    }
+
+    fn sent(&self) -> Vec<f32> {
+        let scores = block_on(self.inner.fetch_scores()).unwrap();
+        scores.iter().map(|score| score.score).collect()
+    }
+
+    fn submit(&self, score: f32) -> Result<usize, SubmitError> {
+        block_on(submit(self, difficulty(3), "a", score))
+    }
+}
+
+fn offline<T: 'static>() -> future::LocalBoxFuture<'static, anyhow::Result<T>> {
+    future::ready(Err(anyhow::anyhow!("offline"))).boxed_local()
+}
+
+impl LeaderboardBackend for FlakyBackend {
+    fn id(&self) -> &str {
+        self.id
+    }
+
+    fn register_player(&self, name: &str) -> future::LocalBoxFuture<'_, anyhow::Result<Player>> {
+        if self.offline.get() {
+            return offline();
+        }
+        self.inner.register_player(name)
+    }
+
+    fn submit_score(
+        &self,
+        player: &Player,
+        score: f32,
+        meta: &str,
+    ) -> future::LocalBoxFuture<'_, anyhow::Result<()>> {
+        if self.offline.get() || self.failing_score.get() == Some(score) {
+            return offline();
+        }
+        self.inner.submit_score(player, score, meta)
+    }
+
+    fn fetch_scores(&self) -> future::LocalBoxFuture<'_, anyhow::Result<Vec<Score>>> {
+        if self.offline.get() || self.failing_fetch.get() {
+            return offline();
+        }
+        self.inner.fetch_scores()
+    }
+}
+
+#[test]
+fn failed_scores_are_queued_once() {
+    let backend = FlakyBackend::new("test_queue_once");
+    backend.offline.set(true);
+    assert!(!backend.submit(100.0).unwrap_err().score_was_sent());
+    assert!(!backend.submit(100.0).unwrap_err().score_was_sent());
+    assert!(!backend.submit(200.0).unwrap_err().score_was_sent());
+    assert_eq!(backend.queue(), [100.0, 200.0]);
+}
+
+#[test]
+fn sent_scores_are_removed_from_the_queue() {
+    let backend = FlakyBackend::new("test_queue_removed");
+    backend.offline.set(true);
+    backend.submit(100.0).unwrap_err();
+    backend.submit(200.0).unwrap_err();
+
+    backend.offline.set(false);
+    // Retrying a queued score from the final screen
+    backend.submit(100.0).unwrap();
+    assert_eq!(backend.queue(), [200.0]);
+    block_on(submit_queued(&backend));
+    assert!(backend.queue().is_empty());
+    assert_eq!(backend.sent(), [100.0, 200.0]);
+}
+
+#[test]
+fn queued_scores_stop_sending_at_the_first_failure() {
+    let backend = FlakyBackend::new("test_queue_stops");
+    backend.offline.set(true);
+    for score in [100.0, 200.0, 300.0] {
+        backend.submit(score).unwrap_err();
+    }
+
+    backend.offline.set(false);
+    backend.failing_score.set(Some(200.0));
+    block_on(submit_queued(&backend));
+    assert_eq!(backend.sent(), [100.0]);
+    assert_eq!(backend.queue(), [200.0, 300.0]);
+}
+
+#[test]
+fn failing_to_fetch_after_sending_does_not_queue_the_score() {
+    let backend = FlakyBackend::new("test_queue_fetch");
+    backend.submit(100.0).unwrap();
+
+    backend.failing_score.set(Some(200.0));
+    let error = backend.submit(200.0).unwrap_err();
+    assert!(matches!(error, SubmitError::SendScore(_)));
+    assert!(!error.score_was_sent());
+    assert_eq!(backend.queue(), [200.0]);
+
+    backend.failing_score.set(None);
+    backend.failing_fetch.set(true);
+    let error = backend.submit(300.0).unwrap_err();
+    assert!(error.score_was_sent());
+    assert_eq!(backend.queue(), [200.0]);
+    assert_eq!(backend.sent(), [100.0, 300.0]);
+}
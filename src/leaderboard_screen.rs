@@ -1,20 +1,127 @@
 use super::*;
 
+use leaderboard::LeaderboardEntry;
+
+/// How many rows fit on the screen
+const ROWS: usize = 5;
+
 pub struct LeaderboardScreen {
     geng: Geng,
     assets: Rc<Assets>,
-    top10: Vec<jornet::Score>,
+    config: Rc<Config>,
+    /// Entries of every difficulty
+    entries: Vec<LeaderboardEntry>,
+    /// Whether the leaderboard was unavailable and these are local high scores
+    local: bool,
+    /// Player to look for when jumping to own rank
+    name: String,
+
+    time_scale: usize,
+    game_time: usize,
+    lives: usize,
+    color_matching: bool,
+    coop: bool,
+    /// Shows the daily challenge of this date instead of the chosen difficulty
+    daily: Option<Date>,
+    /// Best entry of every player for the selected difficulty
+    standings: Vec<LeaderboardEntry>,
+    page: usize,
+
     transition: Option<geng::state::Transition>,
 }
 
 impl LeaderboardScreen {
-    pub fn new(geng: &Geng, assets: &Rc<Assets>, top10: Vec<jornet::Score>) -> Self {
-        Self {
-            top10,
+    pub fn new(
+        geng: &Geng,
+        assets: &Rc<Assets>,
+        config: &Rc<Config>,
+        diff: &Difficulty,
+        name: String,
+        entries: Vec<LeaderboardEntry>,
+        local: bool,
+    ) -> Self {
+        let mut result = Self {
             geng: geng.clone(),
             assets: assets.clone(),
+            config: config.clone(),
+            entries,
+            local,
+            name,
+            time_scale: config
+                .time_scale
+                .iter()
+                .position(|x| *x == diff.time_scale)
+                .unwrap_or(0),
+            game_time: diff.game_time_index(config),
+            lives: config
+                .lives
+                .iter()
+                .position(|x| *x == diff.lives)
+                .unwrap_or(0),
+            color_matching: diff.color_matching,
+            coop: diff.coop,
+            daily: diff.daily,
+            standings: Vec::new(),
+            page: 0,
             transition: None,
+        };
+        result.standings = result.current_standings();
+        result
+    }
+
+    /// Fetches the leaderboard, falling back to local high scores if unavailable
+    pub async fn load(
+        geng: Geng,
+        assets: Rc<Assets>,
+        config: Rc<Config>,
+        options: Rc<Options>,
+        diff: Difficulty,
+        name: String,
+    ) -> Self {
+        let entries = match &options.leaderboard {
+            Some(backend) => match leaderboard::fetch(&**backend).await {
+                Ok(entries) => Some(entries),
+                Err(e) => {
+                    log::error!("Failed to fetch scores: {e:#}");
+                    None
+                }
+            },
+            None => None,
+        };
+        let local = entries.is_none();
+        let entries = entries.unwrap_or_else(|| {
+            highscores::load()
+                .into_iter()
+                .map(LeaderboardEntry::from)
+                .collect()
+        });
+        Self::new(&geng, &assets, &config, &diff, name, entries, local)
+    }
+
+    fn diff(&self) -> Difficulty {
+        if let Some(date) = self.daily {
+            return daily::difficulty(&self.config, date);
         }
+        // Practice and versus runs are never submitted
+        Difficulty {
+            color_matching: self.color_matching,
+            coop: self.coop,
+            ..Difficulty::new(&self.config, self.time_scale, self.game_time, self.lives)
+        }
+    }
+
+    fn current_standings(&self) -> Vec<LeaderboardEntry> {
+        leaderboard::standings(&self.entries, &self.diff())
+    }
+
+    fn pages(&self) -> usize {
+        ((self.standings.len() + ROWS - 1) / ROWS).max(1)
+    }
+
+    fn own_rank(&self) -> Option<usize> {
+        self.standings
+            .iter()
+            .position(|entry| entry.name == self.name)
     }
 }
 
@@ -35,15 +142,150 @@ impl geng::State for LeaderboardScreen {
         if back.was_clicked() {
             self.transition = Some(geng::state::Transition::Pop);
         }
+
+        let mut diff_changed = false;
+        let game_time = ui::TextureButton::new(
+            cx,
+            self.assets.difficulty.game_time_texture(self.game_time),
+            &self.assets.ui_sfx,
+        );
+        if game_time.was_clicked() {
+            self.game_time = (self.game_time + 1) % (self.config.game_time.len() + 1);
+            diff_changed = true;
+        }
+        let time_scale = ui::TextureButton::new(
+            cx,
+            &self.assets.difficulty.time_scale[self.time_scale],
+            &self.assets.ui_sfx,
+        );
+        if time_scale.was_clicked() {
+            self.time_scale = (self.time_scale + 1) % self.config.time_scale.len();
+            diff_changed = true;
+        }
+        let lives = ui::TextureButton::new(
+            cx,
+            &self.assets.difficulty.lives[self.lives],
+            &self.assets.ui_sfx,
+        );
+        if lives.was_clicked() {
+            self.lives = (self.lives + 1) % self.config.lives.len();
+            diff_changed = true;
+        }
+        let color_matching = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            match self.color_matching {
+                true => "colors on",
+                false => "colors off",
+            }
+            .to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if color_matching.was_clicked() {
+            self.color_matching = !self.color_matching;
+            diff_changed = true;
+        }
+        let coop = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            match self.coop {
+                true => "coop on",
+                false => "coop off",
+            }
+            .to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if coop.was_clicked() {
+            self.coop = !self.coop;
+            diff_changed = true;
+        }
+        let daily = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            match self.daily {
+                Some(_) => "daily on",
+                None => "daily off",
+            }
+            .to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if daily.was_clicked() {
+            self.daily = match self.daily {
+                Some(_) => None,
+                None => Some(Date::today()),
+            };
+            diff_changed = true;
+        } else if diff_changed {
+            // Picking a difficulty leaves the daily challenge
+            self.daily = None;
+        }
+        if diff_changed {
+            self.standings = self.current_standings();
+            self.page = 0;
+        }
+
+        let prev = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            "prev".to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if prev.was_clicked() {
+            self.page = self.page.saturating_sub(1);
+        }
+        let next = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            "next".to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if next.was_clicked() {
+            self.page = (self.page + 1).min(self.pages() - 1);
+        }
+        let me = ui::TextButton::new(cx, &self.assets.font, "me".to_owned(), &self.assets.ui_sfx);
+        if me.was_clicked() {
+            if let Some(rank) = self.own_rank() {
+                self.page = rank / ROWS;
+            }
+        }
+        let title = ui::Text::new(&self.assets.font, "leaderboard".to_owned());
+        let page = ui::Text::new(
+            &self.assets.font,
+            format!("{}:{}", self.page + 1, self.pages()),
+        );
+
         let mut stack = stack![
-            ui::TextureWidget::new(&self.assets.leaderboard_background),
+            ui::TextureWidget::new(&self.assets.screen_background),
+            title.fixed_size(vec2(0.0, 16.0)).place(200, 85),
             back.place(25, 235),
+            game_time.place(140, 245),
+            time_scale.place(215, 245),
+            lives.place(290, 245),
+            color_matching.fixed_size(vec2(73.0, 10.0)).place(215, 285),
+            daily.fixed_size(vec2(73.0, 10.0)).place(290, 285),
+            coop.fixed_size(vec2(73.0, 10.0)).place(140, 285),
+            prev.fixed_size(vec2(48.0, 12.0)).place(25, 118),
+            page.fixed_size(vec2(0.0, 12.0)).place(200, 118),
+            next.fixed_size(vec2(48.0, 12.0)).place(327, 118),
         ];
+        if self.own_rank().is_some() {
+            stack.push(Box::new(me.fixed_size(vec2(24.0, 12.0)).place(110, 118)));
+        }
+        if self.local {
+            let local = ui::Text::right_align(&self.assets.font, "local".to_owned());
+            stack.push(Box::new(local.fixed_size(vec2(0.0, 12.0)).place(385, 5)));
+        }
         let mut y = 140;
-        for (rank, score) in self.top10.iter().enumerate() {
+        for (rank, entry) in self
+            .standings
+            .iter()
+            .enumerate()
+            .skip(self.page * ROWS)
+            .take(ROWS)
+        {
             let rank = rank + 1;
-            let name = &score.player;
-            let score = (score.score.floor() as i32).to_string();
+            let name = &entry.name;
+            let score = (entry.score.floor() as i32).to_string();
             let name = ui::Text::left_align(&self.assets.font, format!("#{rank}: {name}"));
             stack.push(Box::new(name.fixed_size(vec2(0.0, 16.0)).place(25, y)));
             let score = ui::Text::right_align(&self.assets.font, score);
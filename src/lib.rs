@@ -1,33 +1,77 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 use geng::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
+mod achievements;
+mod achievements_screen;
 mod assets;
+mod bot;
 mod camera;
 mod config;
+mod daily;
 mod draw3d;
 mod final_screen;
 mod font;
 mod game;
-#[cfg(feature = "leaderboard")]
+mod ghosts;
+mod highscores;
 mod leaderboard;
-#[cfg(feature = "leaderboard")]
 mod leaderboard_screen;
 mod main_menu;
+mod race;
+mod race_lobby;
+mod simulation;
+#[cfg(test)]
+mod testing;
 mod ui;
 mod util;
+mod versus_screen;
 
 use assets::*;
 use camera::*;
 use config::*;
 use draw3d::Draw3d;
 use font::*;
-use game::Game;
+use game::{AttractMode, Game, Replay, ReplayState, RunMode};
 use main_menu::MainMenu;
 use ui::WidgetExt;
 use util::*;
 
 #[derive(clap::Parser)]
 struct Args {
+    /// Seed for the game simulation, random for every run if not specified
+    #[clap(long)]
+    seed: Option<u64>,
+    /// Save the inputs of every finished run to this file
+    #[clap(long)]
+    record_replay: Option<std::path::PathBuf>,
+    /// Play back a replay file instead of starting from the main menu
+    #[clap(long)]
+    replay: Option<std::path::PathBuf>,
+    /// Directory to load skin packs from instead of the per-user one
+    #[clap(long)]
+    skin_packs: Option<std::path::PathBuf>,
+    /// Leaderboard backend to use instead of the one in config.toml
+    #[clap(long, value_enum)]
+    leaderboard: Option<LeaderboardBackendKind>,
+    /// Jornet server to submit scores to
+    #[clap(long)]
+    leaderboard_host: Option<String>,
+    /// Jornet leaderboard id
+    #[clap(long)]
+    leaderboard_id: Option<String>,
+    /// Jornet leaderboard key
+    #[clap(long)]
+    leaderboard_key: Option<String>,
+    /// File to keep scores in when using the file backend
+    #[clap(long)]
+    leaderboard_path: Option<std::path::PathBuf>,
+    /// Relay server to race other players on, like ws://127.0.0.1:1155
+    #[clap(long)]
+    relay: Option<String>,
+    /// Print the bot's average score over this many runs for every difficulty, then exit
+    #[clap(long)]
+    estimate_scores: Option<usize>,
     #[clap(flatten)]
     geng: geng::CliArgs,
 }
@@ -36,6 +80,13 @@ pub fn run() {
     logger::init();
     geng::setup_panic_handler();
     let args: Args = cli::parse();
+    if let Some(runs) = args.estimate_scores {
+        let config =
+            futures::executor::block_on(Config::load(run_dir().join("assets").join("config.toml")))
+                .unwrap();
+        bot::print_estimates(&Rc::new(config), runs);
+        return;
+    }
     Geng::run_with(
         &{
             let mut options = geng::ContextOptions {
@@ -46,17 +97,60 @@ pub fn run() {
             options.with_cli(&args.geng);
             options
         },
-        |geng| async move {
-            let assets: Rc<Assets> = geng
+        move |geng| async move {
+            let mut assets: Assets = geng
                 .asset_manager()
                 .load(run_dir().join("assets"))
                 .await
                 .unwrap();
-            let config: Config = file::load_detect(run_dir().join("assets").join("config.toml"))
+            if let Some(dir) = args.skin_packs.or_else(skin_packs_dir) {
+                assets.skins.load_packs(geng.asset_manager(), &dir).await;
+            }
+            let assets = Rc::new(assets);
+            let mut config = Config::load(run_dir().join("assets").join("config.toml"))
                 .await
                 .unwrap();
+            if let Some(backend) = args.leaderboard {
+                config.leaderboard.backend = backend;
+            }
+            if let Some(host) = args.leaderboard_host {
+                config.leaderboard.host = Some(host);
+            }
+            if let Some(id) = args.leaderboard_id {
+                config.leaderboard.id = id;
+            }
+            if let Some(key) = args.leaderboard_key {
+                config.leaderboard.key = key;
+            }
+            if let Some(path) = args.leaderboard_path {
+                config.leaderboard.path = path;
+            }
             let config = Rc::new(config);
-            geng.run_state(MainMenu::new(&geng, &assets, &config)).await;
+            let options = Rc::new(Options {
+                seed: args.seed,
+                record_replay: args.record_replay,
+                leaderboard: leaderboard::create_backend(&config.leaderboard),
+                relay: args.relay,
+            });
+            let submit_queued = {
+                let backend = options.leaderboard.clone();
+                async move {
+                    if let Some(backend) = backend {
+                        leaderboard::submit_queued(&*backend).await;
+                    }
+                }
+            };
+            let run_state = async {
+                if let Some(path) = args.replay {
+                    let replay: Replay = file::load_detect(path).await.unwrap();
+                    geng.run_state(ReplayState::new(&geng, &assets, &config, &options, replay))
+                        .await;
+                } else {
+                    geng.run_state(MainMenu::new(&geng, &assets, &config, &options))
+                        .await;
+                }
+            };
+            future::join(submit_queued, run_state).await;
         },
     );
 }
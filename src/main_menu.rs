@@ -1,40 +1,121 @@
 use super::*;
 
+use achievements_screen::AchievementsScreen;
+use leaderboard_screen::LeaderboardScreen;
+use race_lobby::RaceLobby;
+
 pub struct MainMenu {
     geng: Geng,
     assets: Rc<Assets>,
     config: Rc<Config>,
+    options: Rc<Options>,
 
     time_scale: usize,
     game_time: usize,
     lives: usize,
+    color_matching: bool,
+    practice: bool,
+    coop: bool,
+    versus: bool,
     name: String,
     changing_name: bool,
+    /// Index into [Skins::names]
+    skin: usize,
+    /// Skin of the second player in co-op or versus, index into [Skins::names]
+    partner_skin: usize,
+    /// Seconds without input, the attract mode demo starts after [BotConfig::attract_delay]
+    idle_time: f32,
+    /// Whether today's daily challenge was played, read when the menu is shown
+    daily_played: bool,
+    /// Set when another state gets pushed on top, cleared once back in the menu
+    covered: bool,
 
     transition: Option<geng::state::Transition>,
     name_aabb: Aabb2<f64>,
 }
 
 impl MainMenu {
-    pub fn new(geng: &Geng, assets: &Rc<Assets>, config: &Rc<Config>) -> Self {
+    pub fn new(
+        geng: &Geng,
+        assets: &Rc<Assets>,
+        config: &Rc<Config>,
+        options: &Rc<Options>,
+    ) -> Self {
         Self {
             geng: geng.clone(),
             assets: assets.clone(),
             config: config.clone(),
+            options: options.clone(),
             time_scale: 0,
             game_time: 0,
             lives: 0,
+            color_matching: false,
+            practice: false,
+            coop: false,
+            versus: false,
             transition: None,
             changing_name: false,
             name: preferences::load("name").unwrap_or("you".to_owned()),
+            skin: preferences::load::<String>("skin")
+                .and_then(|skin| assets.skins.names().iter().position(|name| *name == skin))
+                .unwrap_or(0),
+            partner_skin: preferences::load::<String>("partner_skin")
+                .and_then(|skin| assets.skins.names().iter().position(|name| *name == skin))
+                .unwrap_or(0),
+            idle_time: 0.0,
+            daily_played: daily::played(Date::today()),
+            covered: false,
             name_aabb: Aabb2::ZERO,
         }
     }
+
+    fn diff(&self) -> Difficulty {
+        Difficulty {
+            color_matching: self.color_matching,
+            practice: self.practice,
+            coop: self.coop,
+            versus: self.versus,
+            ..Difficulty::new(&self.config, self.time_scale, self.game_time, self.lives)
+        }
+    }
+
+    fn profile(&self) -> Profile {
+        Profile {
+            name: self.name.clone(),
+            skin: self.assets.skins.names()[self.skin].clone(),
+        }
+    }
+
+    /// Everyone playing the next game
+    fn profiles(&self) -> Vec<Profile> {
+        let mut profiles = vec![self.profile()];
+        if self.coop || self.versus {
+            profiles.push(Profile {
+                name: "p2".to_owned(),
+                skin: self.assets.skins.names()[self.partner_skin].clone(),
+            });
+        }
+        profiles
+    }
+}
+
+/// Skin texture scaled to fit a square, keeping aspect ratio
+fn skin_preview(texture: &ugli::Texture, x: i32, y: i32) -> impl geng::ui::Widget + '_ {
+    use geng::ui::*;
+    const SIZE: f64 = 26.0;
+    let size = texture.size().map(|x| x as f64);
+    let size = size * (SIZE / size.x.max(size.y));
+    let offset = ((vec2::splat(SIZE) - size) / 2.0).map(|x| x as i32);
+    ui::TextureWidget::new(texture)
+        .fixed_size(size)
+        .place(x + offset.x, y + offset.y)
 }
 
 impl geng::State for MainMenu {
     fn transition(&mut self) -> Option<geng::state::Transition> {
-        self.transition.take()
+        let transition = self.transition.take();
+        self.covered |= transition.is_some();
+        transition
     }
     fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
         self.geng
@@ -43,7 +124,32 @@ impl geng::State for MainMenu {
         ugli::clear(framebuffer, Some(Rgba::BLACK), None, None);
     }
 
+    fn update(&mut self, delta_time: f64) {
+        if std::mem::take(&mut self.covered) {
+            // The daily challenge may have been played in the meantime
+            self.daily_played = daily::played(Date::today());
+        }
+        self.idle_time += delta_time as f32;
+        if self.idle_time > self.config.bot.attract_delay {
+            self.idle_time = 0.0;
+            let diff = Difficulty {
+                practice: false,
+                ..self.diff().solo()
+            };
+            self.transition = Some(geng::state::Transition::Push(Box::new(AttractMode::new(
+                &self.geng,
+                &self.assets,
+                &self.config,
+                &self.options,
+                diff,
+                self.profile(),
+                thread_rng().gen(),
+            ))));
+        }
+    }
+
     fn handle_event(&mut self, event: geng::Event) {
+        self.idle_time = 0.0;
         match event {
             geng::Event::EditText(text) => {
                 self.name = text;
@@ -64,11 +170,11 @@ impl geng::State for MainMenu {
         use geng::ui::*;
         let game_time = ui::TextureButton::new(
             cx,
-            &self.assets.difficulty.game_time[self.game_time],
+            self.assets.difficulty.game_time_texture(self.game_time),
             &self.assets.ui_sfx,
         );
         if game_time.was_clicked() {
-            self.game_time = (self.game_time + 1) % self.config.game_time.len();
+            self.game_time = (self.game_time + 1) % (self.config.game_time.len() + 1);
         }
         let time_scale = ui::TextureButton::new(
             cx,
@@ -86,6 +192,58 @@ impl geng::State for MainMenu {
         if lives.was_clicked() {
             self.lives = (self.lives + 1) % self.config.lives.len();
         }
+        let color_matching = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            match self.color_matching {
+                true => "colors on",
+                false => "colors off",
+            }
+            .to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if color_matching.was_clicked() {
+            self.color_matching = !self.color_matching;
+        }
+        let practice = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            match self.practice {
+                true => "practice on",
+                false => "practice off",
+            }
+            .to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if practice.was_clicked() {
+            self.practice = !self.practice;
+        }
+        let daily = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            match self.daily_played {
+                true => "daily done",
+                false => "daily",
+            }
+            .to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if daily.was_clicked() {
+            let date = Date::today();
+            self.geng.window().stop_text_edit();
+            self.transition = Some(geng::state::Transition::Push(Box::new(Game::new(
+                &self.geng,
+                &self.assets,
+                &self.config,
+                &self.options,
+                daily::attempt(&self.config, date),
+                vec![self.profile()],
+                daily::seed(date),
+                RunMode::played(),
+            ))));
+            preferences::save("name", &self.name);
+            preferences::save("skin", &self.profile().skin);
+        }
         let play = ui::TextureButton::new(cx, &self.assets.play_button, &self.assets.ui_sfx);
         if play.was_clicked() {
             self.geng.window().stop_text_edit();
@@ -93,14 +251,127 @@ impl geng::State for MainMenu {
                 &self.geng,
                 &self.assets,
                 &self.config,
-                Difficulty {
-                    time_scale: self.config.time_scale[self.time_scale],
-                    game_time: self.config.game_time[self.game_time],
-                    lives: self.config.lives[self.lives],
-                },
-                self.name.clone(),
+                &self.options,
+                self.diff(),
+                self.profiles(),
+                self.options.game_seed(),
+                RunMode::played(),
             ))));
             preferences::save("name", &self.name);
+            preferences::save("skin", &self.profile().skin);
+            if self.coop || self.versus {
+                preferences::save(
+                    "partner_skin",
+                    &self.assets.skins.names()[self.partner_skin],
+                );
+            }
+        }
+        let race = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            "race".to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if race.was_clicked() {
+            self.geng.window().stop_text_edit();
+            self.transition = Some(geng::state::Transition::Push(Box::new(
+                geng::LoadingScreen::new(
+                    &self.geng,
+                    geng::EmptyLoadingScreen::new(&self.geng),
+                    RaceLobby::join(
+                        self.geng.clone(),
+                        self.assets.clone(),
+                        self.config.clone(),
+                        self.options.clone(),
+                        // Everyone rides alone in a race
+                        self.diff().solo(),
+                        self.profile(),
+                    ),
+                ),
+            )));
+            preferences::save("name", &self.name);
+            preferences::save("skin", &self.profile().skin);
+        }
+        let skin_count = self.assets.skins.names().len();
+        let prev_skin =
+            ui::TextButton::new(cx, &self.assets.font, "-".to_owned(), &self.assets.ui_sfx);
+        if prev_skin.was_clicked() {
+            self.skin = (self.skin + skin_count - 1) % skin_count;
+        }
+        let next_skin =
+            ui::TextButton::new(cx, &self.assets.font, "+".to_owned(), &self.assets.ui_sfx);
+        if next_skin.was_clicked() {
+            self.skin = (self.skin + 1) % skin_count;
+        }
+        let players = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            match (self.coop, self.versus) {
+                (true, _) => "coop",
+                (_, true) => "versus",
+                _ => "solo",
+            }
+            .to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if players.was_clicked() {
+            // Solo, then co-op, then versus
+            (self.coop, self.versus) = match (self.coop, self.versus) {
+                (false, false) => (true, false),
+                (true, _) => (false, true),
+                (false, true) => (false, false),
+            };
+        }
+        let partner_skin_name = &self.assets.skins.names()[self.partner_skin];
+        let partner_skin = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            format!(
+                "p2 {}",
+                self.assets
+                    .skins
+                    .get(partner_skin_name)
+                    .display_name
+                    .to_lowercase()
+            ),
+            &self.assets.ui_sfx,
+        );
+        if partner_skin.was_clicked() {
+            self.partner_skin = (self.partner_skin + 1) % skin_count;
+        }
+        let skin = self.assets.skins.get(&self.assets.skins.names()[self.skin]);
+        let skin_name = ui::Text::new(&self.assets.font, skin.display_name.to_lowercase());
+
+        let leaderboard_button =
+            ui::TextureButton::new(cx, &self.assets.leaderboard_button, &self.assets.ui_sfx);
+        if leaderboard_button.was_clicked() {
+            self.geng.window().stop_text_edit();
+            self.transition = Some(geng::state::Transition::Push(Box::new(
+                geng::LoadingScreen::new(
+                    &self.geng,
+                    geng::EmptyLoadingScreen::new(&self.geng),
+                    LeaderboardScreen::load(
+                        self.geng.clone(),
+                        self.assets.clone(),
+                        self.config.clone(),
+                        self.options.clone(),
+                        self.diff(),
+                        self.name.clone(),
+                    ),
+                ),
+            )));
+        }
+        let achievements = ui::TextButton::new(
+            cx,
+            &self.assets.font,
+            "achievements".to_owned(),
+            &self.assets.ui_sfx,
+        );
+        if achievements.was_clicked() {
+            self.geng.window().stop_text_edit();
+            self.transition = Some(geng::state::Transition::Push(Box::new(
+                AchievementsScreen::new(&self.geng, &self.assets, &self.config),
+            )));
         }
         let name = ui::TextInput::new(
             cx,
@@ -120,13 +391,36 @@ impl geng::State for MainMenu {
             game_time.place(300, 95),
             time_scale.place(300, 133),
             lives.place(300, 170),
+            color_matching.fixed_size(vec2(73.0, 10.0)).place(300, 208),
             play.place(180, 220),
+            leaderboard_button.place(35, 172),
+            skin_name.fixed_size(vec2(0.0, 10.0)).place(360, 4),
+            skin_preview(&skin.hand, 333, 16),
+            skin_preview(&skin.bike, 361, 16),
+            skin_preview(&skin.bag, 333, 44),
+            skin_preview(&skin.newspaper, 361, 44),
+            prev_skin.fixed_size(vec2(10.0, 12.0)).place(321, 36),
+            next_skin.fixed_size(vec2(10.0, 12.0)).place(389, 36),
+            name.fixed_size(vec2(160.0, 16.0)).place(20, 260),
         ];
-
-        if cfg!(feature = "leaderboard") {
-            stack.push(Box::new(name.fixed_size(vec2(160.0, 16.0)).place(20, 260)));
+        // Online races need a relay to race on
+        if self.options.relay.is_some() {
+            stack.push(Box::new(race.fixed_size(vec2(73.0, 10.0)).place(300, 222)));
         }
-
-        stack.center().boxed()
+        if self.coop || self.versus {
+            stack.push(Box::new(
+                partner_skin.fixed_size(vec2(78.0, 8.0)).place(321, 74),
+            ));
+        }
+        // Mode buttons get a row of their own under the artwork
+        let modes = row![
+            practice.fixed_size(vec2(96.0, 10.0)).uniform_padding(4.0),
+            daily.fixed_size(vec2(80.0, 10.0)).uniform_padding(4.0),
+            achievements
+                .fixed_size(vec2(96.0, 10.0))
+                .uniform_padding(4.0),
+            players.fixed_size(vec2(64.0, 10.0)).uniform_padding(4.0),
+        ];
+        column![stack, modes.center()].center().boxed()
     }
 }
@@ -0,0 +1,166 @@
+//! Client side of online races, see [juggle_mail_relay]
+
+use super::*;
+
+use std::collections::BTreeMap;
+
+use juggle_mail_relay::{ClientId, ClientMessage, ServerMessage};
+
+type Connection = geng::net::client::Connection<ServerMessage, ClientMessage>;
+
+/// Seconds between pings while waiting for the race to start
+const PING_INTERVAL: f32 = 1.0;
+
+/// Another player in the same race, only known through what the relay passes along
+#[derive(Debug, Clone)]
+pub struct Rival {
+    pub name: String,
+    pub score: f32,
+    pub lives: usize,
+    /// Score of the last delivery and how long ago it was, shown for a moment
+    pub last_delivery: Option<(f32, f32)>,
+    pub finished: bool,
+}
+
+pub struct Race {
+    connection: Connection,
+    /// Given by the relay once connected
+    pub id: Option<ClientId>,
+    /// Ordered by id so that the HUD does not shuffle them around
+    pub rivals: BTreeMap<ClientId, Rival>,
+    /// Seed and countdown once the relay started the race,
+    /// the countdown is shortened by the time the start took to get here
+    pub start: Option<(u64, f32)>,
+    /// Time since joining, pings carry it
+    clock: f32,
+    /// Shortest round trip to the relay and back measured so far
+    round_trip: Option<f32>,
+    ping_timer: f32,
+    /// Last progress sent and the time until sending again
+    sent_progress: Option<(i32, usize)>,
+    progress_timer: f32,
+}
+
+impl Race {
+    /// Asks the relay for a race against players with the same difficulty
+    pub async fn join(address: &str, name: &str, diff: &Difficulty) -> anyhow::Result<Self> {
+        let mut connection: Connection = geng::net::client::connect(address).await?;
+        // Before joining, so that the pong comes back before the race can start
+        connection.send(ClientMessage::Ping { time: 0.0 });
+        connection.send(ClientMessage::Join {
+            name: name.to_owned(),
+            room: serde_json::to_string(diff)?,
+        });
+        Ok(Self {
+            connection,
+            id: None,
+            rivals: BTreeMap::new(),
+            start: None,
+            clock: 0.0,
+            round_trip: None,
+            ping_timer: PING_INTERVAL,
+            sent_progress: None,
+            progress_timer: 0.0,
+        })
+    }
+
+    /// Handles everything the relay sent since the last call
+    pub fn update(&mut self, delta_time: f32) {
+        self.clock += delta_time;
+        if self.start.is_none() {
+            self.ping_timer -= delta_time;
+            if self.ping_timer < 0.0 {
+                self.ping_timer = PING_INTERVAL;
+                self.connection
+                    .send(ClientMessage::Ping { time: self.clock });
+            }
+        }
+        for rival in self.rivals.values_mut() {
+            if let Some((_score, time)) = &mut rival.last_delivery {
+                *time += delta_time;
+            }
+        }
+        for message in self.connection.new_messages() {
+            let message = match message {
+                Ok(message) => message,
+                Err(e) => {
+                    log::error!("Race connection failed: {e:#}");
+                    continue;
+                }
+            };
+            match message {
+                ServerMessage::Welcome { id } => self.id = Some(id),
+                ServerMessage::Joined { id, name } => {
+                    if Some(id) != self.id {
+                        self.rivals.insert(
+                            id,
+                            Rival {
+                                name,
+                                score: 0.0,
+                                lives: 0,
+                                last_delivery: None,
+                                finished: false,
+                            },
+                        );
+                    }
+                }
+                // Finished rivals leave, their final score stays
+                ServerMessage::Left { id } => {
+                    self.rivals
+                        .retain(|rival_id, rival| *rival_id != id || rival.finished);
+                }
+                ServerMessage::Pong { time } => {
+                    let round_trip = self.clock - time;
+                    self.round_trip = Some(
+                        self.round_trip
+                            .map_or(round_trip, |best| best.min(round_trip)),
+                    );
+                }
+                ServerMessage::Start { seed, countdown } => {
+                    // Sent by the relay half a round trip ago
+                    let delay = self.round_trip.map_or(0.0, |round_trip| round_trip / 2.0);
+                    self.start = Some((seed, countdown - delay));
+                }
+                ServerMessage::Progress { id, score, lives } => {
+                    if let Some(rival) = self.rivals.get_mut(&id) {
+                        rival.score = score;
+                        rival.lives = lives;
+                    }
+                }
+                ServerMessage::Delivered { id, score } => {
+                    if let Some(rival) = self.rivals.get_mut(&id) {
+                        rival.last_delivery = Some((score, 0.0));
+                    }
+                }
+                ServerMessage::Finished { id, score } => {
+                    if let Some(rival) = self.rivals.get_mut(&id) {
+                        rival.score = score;
+                        rival.finished = true;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sends own score and lives, at most every [Config::race_progress_interval]
+    /// and only when they changed
+    pub fn report(&mut self, config: &Config, delta_time: f32, score: f32, lives: usize) {
+        self.progress_timer -= delta_time;
+        let progress = (score.floor() as i32, lives);
+        if self.progress_timer > 0.0 || self.sent_progress == Some(progress) {
+            return;
+        }
+        self.progress_timer = config.race_progress_interval;
+        self.sent_progress = Some(progress);
+        self.connection
+            .send(ClientMessage::Progress { score, lives });
+    }
+
+    pub fn delivered(&mut self, score: f32) {
+        self.connection.send(ClientMessage::Delivered { score });
+    }
+
+    pub fn finished(&mut self, score: f32) {
+        self.connection.send(ClientMessage::Finished { score });
+    }
+}
@@ -0,0 +1,123 @@
+use super::*;
+
+use race::Race;
+
+/// Waits for the relay to start an online race, then counts down into the game
+pub struct RaceLobby {
+    geng: Geng,
+    assets: Rc<Assets>,
+    config: Rc<Config>,
+    options: Rc<Options>,
+    diff: Difficulty,
+    profile: Profile,
+    /// [None] if could not connect, or once the game started
+    race: Option<Race>,
+    /// Time left until the run begins, once the race started
+    countdown: Option<f32>,
+
+    transition: Option<geng::state::Transition>,
+}
+
+impl RaceLobby {
+    /// Connects to the relay in [Options::relay]
+    pub async fn join(
+        geng: Geng,
+        assets: Rc<Assets>,
+        config: Rc<Config>,
+        options: Rc<Options>,
+        diff: Difficulty,
+        profile: Profile,
+    ) -> Self {
+        let race = match &options.relay {
+            Some(address) => match Race::join(address, &profile.name, &diff).await {
+                Ok(race) => Some(race),
+                Err(e) => {
+                    log::error!("Failed to join a race: {e:#}");
+                    None
+                }
+            },
+            None => None,
+        };
+        Self {
+            geng,
+            assets,
+            config,
+            options,
+            diff,
+            profile,
+            race,
+            countdown: None,
+            transition: None,
+        }
+    }
+}
+
+impl geng::State for RaceLobby {
+    fn transition(&mut self) -> Option<geng::state::Transition> {
+        self.transition.take()
+    }
+    fn update(&mut self, delta_time: f64) {
+        let delta_time = delta_time as f32;
+        let Some(race) = &mut self.race else {
+            return;
+        };
+        race.update(delta_time);
+        if let Some(countdown) = &mut self.countdown {
+            *countdown -= delta_time;
+        } else if let Some((_seed, countdown)) = race.start {
+            self.countdown = Some(countdown);
+        }
+        if self.countdown.is_some_and(|countdown| countdown <= 0.0) {
+            let race = self.race.take().unwrap();
+            let (seed, _countdown) = race.start.unwrap();
+            self.transition = Some(geng::state::Transition::Switch(Box::new(
+                Game::new(
+                    &self.geng,
+                    &self.assets,
+                    &self.config,
+                    &self.options,
+                    self.diff.clone(),
+                    vec![self.profile.clone()],
+                    seed,
+                    RunMode::played(),
+                )
+                .with_race(race),
+            )));
+        }
+    }
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        self.geng
+            .window()
+            .set_cursor_type(geng::CursorType::Default);
+        ugli::clear(framebuffer, Some(Rgba::BLACK), None, None);
+    }
+
+    fn ui<'a>(&'a mut self, cx: &'a geng::ui::Controller) -> Box<dyn geng::ui::Widget + 'a> {
+        use geng::ui::*;
+        let back = ui::TextureButton::new(cx, &self.assets.back, &self.assets.ui_sfx);
+        if back.was_clicked() {
+            self.transition = Some(geng::state::Transition::Pop);
+        }
+        let status = match (&self.race, self.countdown) {
+            (None, _) => "could not connect".to_owned(),
+            (Some(_), Some(countdown)) => format!("starting in {}", countdown.ceil() as i32),
+            (Some(_), None) => "waiting for rivals".to_owned(),
+        };
+        let status = ui::Text::new(&self.assets.font, status);
+
+        let mut stack = stack![
+            ui::TextureWidget::new(&self.assets.screen_background),
+            status.fixed_size(vec2(0.0, 16.0)).place(200, 85),
+            back.place(25, 235),
+        ];
+        let rivals = self.race.iter().flat_map(|race| race.rivals.values());
+        for (row, rival) in rivals.enumerate() {
+            let name = ui::Text::new(&self.assets.font, rival.name.clone());
+            stack.push(Box::new(
+                name.fixed_size(vec2(0.0, 12.0))
+                    .place(200, 110 + row as i32 * 13),
+            ));
+        }
+        stack.center().boxed()
+    }
+}
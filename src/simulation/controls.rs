@@ -0,0 +1,188 @@
+use super::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TouchKind {
+    Start,
+    Move,
+    End,
+}
+
+/// Single input event, position is in world (ui camera) coordinates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TouchInput {
+    pub kind: TouchKind,
+    pub id: Option<u64>,
+    pub position: vec2<f32>,
+    /// Index into [Simulation::players], only matters for new touches
+    #[serde(default)]
+    pub player: usize,
+}
+
+impl Simulation {
+    pub fn input(&mut self, input: TouchInput) {
+        match input.kind {
+            TouchKind::Start => self.touch_start(input.id, input.player, input.position),
+            TouchKind::Move => self.touch_move(input.id, input.player, input.position),
+            TouchKind::End => self.touch_end(input.id, input.position),
+        }
+    }
+
+    pub fn hovered_item(&self, cursor_world: vec2<f32>) -> Option<usize> {
+        self.juggling_items
+            .iter()
+            .enumerate()
+            .filter(|(_index, item)| {
+                Aabb2::ZERO.extend_uniform(1.0).contains(
+                    (Quad::unit()
+                        .scale(
+                            (item.half_size * self.config.items[item.kind].hitbox)
+                                .map(|x| x + self.config.hand_radius),
+                        )
+                        .rotate(Angle::from_radians(item.rot))
+                        .translate(item.pos)
+                        .transform
+                        .inverse()
+                        * cursor_world.extend(1.0))
+                    .into_2d(),
+                )
+            })
+            .min_by_key(|(_index, item)| r32((item.pos - cursor_world).len()))
+            .map(|(index, _item)| index)
+    }
+
+    pub fn touch_start(&mut self, id: Option<u64>, player: usize, position: vec2<f32>) {
+        if self.game_over {
+            return;
+        }
+        if self
+            .touches
+            .iter()
+            .any(|touch| touch.id == id && touch.holding.is_some())
+        {
+            return;
+        }
+        self.touches.retain(|touch| touch.id != id);
+        let mut touch = Touch {
+            id,
+            player,
+            position,
+            holding: None,
+            error_animation_time: 1.0,
+            throw_animation_time: 1.0,
+            remove_time: None,
+        };
+        let cursor_world = touch.position;
+        let hovered = self.hovered_item(touch.position);
+        if let Some(index) =
+            hovered.filter(|&index| self.diff.versus && self.juggling_items[index].player != player)
+        {
+            // Rival's item gets knocked down instead of picked up
+            let item = &mut self.juggling_items[index];
+            let away = (item.pos.x - cursor_world.x).signum();
+            item.vel = vec2(away, -1.0) * self.config.rival_knock_speed;
+            touch.throw_animation_time = 0.0;
+            self.events.push(Event::Knocked { pos: item.pos });
+        } else if let Some(index) = hovered {
+            self.events.push(Event::Picked);
+            let mut item = self.juggling_items.remove(index);
+            item.player = player;
+            touch.holding = Some(item);
+        } else if self.players[player]
+            .bag_position
+            .extend_uniform(self.config.hand_radius)
+            .contains(cursor_world)
+        {
+            self.events.push(Event::Picked);
+            let mut item = self.pull_item();
+            item.player = player;
+            touch.holding = Some(item);
+        } else {
+            touch.error_animation_time = 0.0;
+            self.events.push(Event::PickFailed);
+        }
+        self.touches.push(touch);
+    }
+
+    pub fn touch_move(&mut self, id: Option<u64>, player: usize, position: vec2<f32>) {
+        if let Some(touch) = self.touches.iter_mut().find(|touch| touch.id == id) {
+            touch.position = position;
+        } else {
+            self.touches.push(Touch {
+                id,
+                player,
+                position,
+                holding: None,
+                error_animation_time: 1.0,
+                throw_animation_time: 1.0,
+                remove_time: None,
+            });
+        }
+    }
+
+    pub fn touch_end(&mut self, id: Option<u64>, position: vec2<f32>) {
+        let Some(touch_index) = self.touches.iter_mut().position(|touch| touch.id == id) else {
+            return;
+        };
+        let mut touch = self.touches.remove(touch_index);
+        touch.position = position;
+        let cursor_world = touch.position;
+        if let Some(mut item) = touch.holding.take() {
+            touch.throw_animation_time = 0.0;
+            let target = match self.hovered_mailbox(touch.position) {
+                Some(index) => {
+                    let mailbox = &self.mailboxes[index];
+                    Some((
+                        mailbox.id,
+                        self.mailbox_pos(mailbox),
+                        self.config.mailbox_size,
+                        self.perfect_aim(touch.position, mailbox),
+                    ))
+                }
+                None => self.hovered_power_up(touch.position).map(|index| {
+                    let power_up = &self.power_ups[index];
+                    let pos = self.power_up_pos(power_up);
+                    (power_up.id, pos, self.config.power_up_size, false)
+                }),
+            };
+            if let Some((to_id, to, size, perfect)) = target {
+                item.w = self.config.item_throw_max_w * to.x.signum();
+                // Shoutout to Foggy's mom
+                let mut pixel_ray = self.camera.pixel_ray(
+                    self.virtual_framebuffer_size(),
+                    self.world_to_virtual_screen(touch.position),
+                );
+                let cam_dir = self.camera.dir();
+                pixel_ray.dir -= cam_dir * vec3::dot(cam_dir, pixel_ray.dir);
+                pixel_ray.dir += cam_dir;
+                let item = ThrownItem {
+                    item,
+                    from: pixel_ray.from + pixel_ray.dir.normalize_or_zero(),
+                    to: to.normalize_or_zero() * (self.config.earth_radius + size),
+                    t: 0.0,
+                    prev_t: 0.0,
+                    to_id,
+                    perfect,
+                };
+                self.thrown_items.push(item);
+                self.events.push(Event::Thrown);
+            } else {
+                item.pos = cursor_world;
+                item.prev_pos = item.pos;
+                item.prev_rot = item.rot;
+                item.vel = (vec2(0.0, self.config.throw_target_height) - item.pos).rotate(
+                    Angle::from_radians(self.rng.gen_range(
+                        -self.config.throw_angle.to_radians()..self.config.throw_angle.to_radians(),
+                    )),
+                ) * self.config.throw_speed
+                    / self.config.throw_target_height;
+                item.w = self.rng.gen_range(-1.0..1.0) * self.config.items[item.kind].spin;
+                self.juggling_items.push(item);
+                self.stats.juggled();
+                self.players[touch.player].stats.juggled();
+                self.events.push(Event::Juggled);
+            }
+        }
+        touch.remove_time = Some(0.0);
+        self.touches.push(touch);
+    }
+}
@@ -0,0 +1,589 @@
+//! Gameplay rules without any window, audio or GPU.
+//! Rendering and sounds are done by [crate::game::Game] in response to [Event]s.
+
+use super::*;
+
+pub type Id = usize;
+
+const VIRTUAL_SCREEN_ASPECT: f32 = 4.0;
+
+mod controls;
+#[cfg(test)]
+mod tests;
+mod update;
+
+pub use controls::{TouchInput, TouchKind};
+
+pub struct Item {
+    /// Index into [Config::items]
+    pub kind: usize,
+    pub pos: vec2<f32>,
+    pub prev_pos: vec2<f32>,
+    pub vel: vec2<f32>,
+    pub rot: f32,
+    pub prev_rot: f32,
+    pub w: f32,
+    pub half_size: vec2<f32>,
+    pub color: Rgba<f32>,
+    /// Index into [Config::mailbox_colors] in color matching mode
+    pub mailbox_color: Option<usize>,
+    /// Who handled it last, gets the score for delivering it
+    pub player: usize,
+}
+
+impl Item {
+    pub fn new(kind: usize, aspect: f32, scale: f32, rng: &mut impl Rng) -> Self {
+        let rot = rng.gen_range(0.0..2.0 * f32::PI);
+        Self {
+            kind,
+            pos: vec2::ZERO,
+            prev_pos: vec2::ZERO,
+            vel: vec2::ZERO,
+            rot,
+            prev_rot: rot,
+            w: 0.0,
+            half_size: vec2(aspect, 1.0) * scale,
+            color: {
+                Rgba::new(
+                    rng.gen_range(0.9..1.0),
+                    rng.gen_range(0.9..1.0),
+                    rng.gen_range(0.9..1.0),
+                    1.0,
+                )
+            },
+            mailbox_color: None,
+            player: 0,
+        }
+    }
+
+    pub fn tint(&self, config: &Config) -> Rgba<f32> {
+        match self.mailbox_color {
+            Some(color) => config.mailbox_colors[color],
+            None => self.color,
+        }
+    }
+
+    /// Position and rotation between the last two simulation steps
+    pub fn interpolated(&self, alpha: f32) -> (vec2<f32>, f32) {
+        (
+            self.prev_pos + (self.pos - self.prev_pos) * alpha,
+            self.prev_rot + (self.rot - self.prev_rot) * alpha,
+        )
+    }
+}
+
+#[derive(Deref)]
+pub struct ThrownItem {
+    #[deref]
+    pub item: Item,
+    pub from: vec3<f32>,
+    pub to: vec3<f32>,
+    pub t: f32,
+    pub prev_t: f32,
+    pub to_id: Id,
+    /// Aimed right at the center of the mailbox
+    pub perfect: bool,
+}
+
+pub struct Mailbox {
+    pub id: Id,
+    pub x: f32,
+    pub latitude: f32,
+    pub color: usize,
+}
+
+/// Lies on the road and grants an effect when hit with a thrown item
+pub struct PowerUp {
+    pub id: Id,
+    pub x: f32,
+    pub latitude: f32,
+    pub kind: PowerUpKind,
+    /// How long the effect lasts
+    pub duration: f32,
+}
+
+pub struct Effect {
+    pub kind: PowerUpKind,
+    pub time_left: f32,
+}
+
+pub struct Hazard {
+    /// Index into [Config::hazards]
+    pub kind: usize,
+    pub x: f32,
+    pub latitude: f32,
+    /// Whether the bike already rode over or past it
+    pub passed: bool,
+}
+
+pub struct House {
+    pub x: f32,
+    pub latitude: f32,
+    pub texture: usize,
+}
+
+/// Counters shown in practice mode
+#[derive(Debug, Default, Clone)]
+pub struct Stats {
+    pub drops: usize,
+    pub deliveries: usize,
+    /// Thrown items that missed or were rejected
+    pub misses: usize,
+    /// Items tossed up in a row without dropping any
+    pub juggle_streak: usize,
+    pub longest_juggle_streak: usize,
+}
+
+impl Stats {
+    fn juggled(&mut self) {
+        self.juggle_streak += 1;
+        self.longest_juggle_streak = self.longest_juggle_streak.max(self.juggle_streak);
+    }
+
+    fn dropped(&mut self) {
+        self.drops += 1;
+        self.juggle_streak = 0;
+    }
+
+    /// Share of throws that got delivered, [None] if nothing was thrown yet
+    pub fn accuracy(&self) -> Option<f32> {
+        let throws = self.deliveries + self.misses;
+        (throws != 0).then(|| self.deliveries as f32 / throws as f32)
+    }
+}
+
+/// One of the players sharing the road
+pub struct Player {
+    pub bag_position: Aabb2<f32>,
+    /// Part of [Simulation::score] that this player made
+    pub score: f32,
+    /// Own lives in versus mode, otherwise [Simulation::lives] are shared
+    pub lives: usize,
+    pub stats: Stats,
+    /// Deliveries in a row without dropping or missing anything
+    pub delivery_streak: usize,
+    /// Time until the streak goes down by one
+    streak_decay: f32,
+    /// Latitude and time of the last delivery, for pair bonuses
+    last_delivery: Option<(f32, f32)>,
+}
+
+/// Animation timers live here too since they are tied to touch lifetime
+pub struct Touch {
+    pub id: Option<u64>,
+    /// Index into [Simulation::players]
+    pub player: usize,
+    pub position: vec2<f32>,
+    pub holding: Option<Item>,
+    pub error_animation_time: f32,
+    pub throw_animation_time: f32,
+    pub remove_time: Option<f32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOverReason {
+    TimeUp,
+    OutOfLives,
+}
+
+/// Things that happened during an update or input, for the presentation to react to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Picked,
+    PickFailed,
+    Juggled,
+    Thrown,
+    Delivered {
+        pos: vec3<f32>,
+        score: f32,
+        /// Whether colors matched in color matching mode
+        color_match: Option<bool>,
+        /// Deliveries in a row including this one
+        streak: usize,
+        /// Other mailbox of the pair got delivered to just before
+        pair: bool,
+        perfect: bool,
+        /// Index into [Simulation::players] of who threw the item
+        player: usize,
+    },
+    /// Juggled item got knocked out of the air by a rival in versus mode
+    Knocked {
+        pos: vec2<f32>,
+    },
+    /// Juggled item fell below the bag
+    Dropped {
+        pos: vec2<f32>,
+    },
+    /// Thrown item arrived at a mailbox that is already gone
+    Missed {
+        pos: vec3<f32>,
+    },
+    /// Thrown item arrived at a mailbox that does not accept its kind
+    Rejected {
+        pos: vec3<f32>,
+    },
+    PowerUpHit {
+        pos: vec3<f32>,
+        kind: PowerUpKind,
+    },
+    /// Bike rode over a hazard
+    HazardHit {
+        /// Index into [Config::hazards]
+        kind: usize,
+    },
+    LifeLost {
+        lives_left: usize,
+    },
+    /// Would have lost a life if not in practice mode
+    LifeSpared,
+    GameOver {
+        reason: GameOverReason,
+    },
+}
+
+/// What the simulation needs to know about the assets without loading them
+pub struct AssetInfo {
+    /// Width to height ratio of the texture of every item kind
+    pub item_aspects: Vec<f32>,
+    pub house_textures: usize,
+}
+
+pub struct Simulation {
+    pub config: Rc<Config>,
+    pub diff: Difficulty,
+    pub asset_info: AssetInfo,
+    rng: StdRng,
+    /// Only used for what spawns along the road, so that the road is the same
+    /// for the same seed no matter how the player plays
+    road_rng: StdRng,
+    /// Number of updates done so far
+    pub tick: u64,
+    /// Time since the start of the run, not affected by time scale
+    pub time: f32,
+    pub score: f32,
+    pub time_left: f32,
+    pub lives: usize,
+    pub game_over: bool,
+    pub stats: Stats,
+    /// Kind of the next item pulled from the bag
+    pub next_kind: usize,
+    /// Color of the next item pulled from the bag in color matching mode
+    pub next_color: Option<usize>,
+    next_id: Id,
+    pub camera: Camera,
+    pub players: Vec<Player>,
+    pub juggling_items: Vec<Item>,
+    pub thrown_items: Vec<ThrownItem>,
+    pub mailboxes: Vec<Mailbox>,
+    pub power_ups: Vec<PowerUp>,
+    /// Active timed effects of power-ups
+    pub effects: Vec<Effect>,
+    pub hazards: Vec<Hazard>,
+    /// Where hazards get a chance to spawn next
+    next_hazard_latitude: f32,
+    pub houses: Vec<House>,
+    pub touches: Vec<Touch>,
+    pub latitude: f32,
+    pub prev_latitude: f32,
+    events: Vec<Event>,
+}
+
+impl Simulation {
+    pub fn new(config: &Rc<Config>, diff: Difficulty, seed: u64, asset_info: AssetInfo) -> Self {
+        let camera = Camera::new(
+            config.fov.to_radians(),
+            config.ui_fov,
+            config.camera_rot.to_radians(),
+            config.earth_radius + config.camera_height,
+        );
+        let mut rng = StdRng::seed_from_u64(seed);
+        let road_rng = StdRng::seed_from_u64(rng.gen());
+        // Bags are spread evenly along the bottom of the screen
+        let screen_width = VIRTUAL_SCREEN_ASPECT * camera.fov();
+        let players = (0..diff.players())
+            .map(|index| {
+                let x = ((index as f32 + 0.5) / diff.players() as f32 - 0.5) * screen_width / 2.0;
+                Player {
+                    bag_position: Aabb2::point(vec2(x, -camera.fov() / 2.0 + 1.0))
+                        .extend_uniform(1.0),
+                    score: 0.0,
+                    lives: diff.lives,
+                    stats: Stats::default(),
+                    delivery_streak: 0,
+                    streak_decay: 0.0,
+                    last_delivery: None,
+                }
+            })
+            .collect();
+        let mut simulation = Self {
+            config: config.clone(),
+            asset_info,
+            rng,
+            road_rng,
+            tick: 0,
+            time: 0.0,
+            score: 0.0,
+            time_left: diff.game_time,
+            lives: diff.lives,
+            game_over: false,
+            stats: Stats::default(),
+            next_kind: 0,
+            next_color: None,
+            diff,
+            next_id: 0,
+            players,
+            camera,
+            juggling_items: vec![],
+            thrown_items: vec![],
+            mailboxes: vec![],
+            power_ups: vec![],
+            effects: vec![],
+            hazards: vec![],
+            next_hazard_latitude: config.spawn_distance.to_radians(),
+            houses: vec![],
+            touches: vec![],
+            latitude: 0.0,
+            prev_latitude: 0.0,
+            events: vec![],
+        };
+        simulation.roll_next_item();
+        simulation
+    }
+
+    /// Chooses what comes out of the bag next
+    fn roll_next_item(&mut self) {
+        let weights = self.config.items.iter().map(|kind| kind.weight);
+        self.next_kind = self
+            .rng
+            .sample(rand::distributions::WeightedIndex::new(weights).unwrap());
+        self.next_color = self.diff.color_matching.then(|| {
+            // Only colors of mailboxes that accept the item
+            let accepted = self.config.accepted_mailboxes(self.next_kind);
+            if accepted.is_empty() {
+                self.rng.gen_range(0..self.config.mailbox_colors.len())
+            } else {
+                accepted[self.rng.gen_range(0..accepted.len())]
+            }
+        });
+    }
+
+    /// Takes an item out of the bag, rolls the next one
+    fn pull_item(&mut self) -> Item {
+        let kind = self.next_kind;
+        let mut item = Item::new(
+            kind,
+            self.asset_info.item_aspects[kind],
+            self.config.item_scale * self.config.items[kind].scale,
+            &mut self.rng,
+        );
+        item.mailbox_color = self.next_color;
+        self.roll_next_item();
+        item
+    }
+
+    /// Events that happened since the last call
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Score gets multiplied by the number of items in play.
+    /// In versus mode only the items of `player` count, [None] counts everyone's
+    pub fn multiplier(&self, player: Option<usize>) -> usize {
+        let counts = |owner: usize| !self.diff.versus || player.map_or(true, |p| p == owner);
+        let multiplier = self
+            .juggling_items
+            .iter()
+            .filter(|item| counts(item.player))
+            .count()
+            + 1
+            + self
+                .touches
+                .iter()
+                .filter(|touch| touch.holding.is_some() && counts(touch.player))
+                .count();
+        if self.effect_active(PowerUpKind::DoubleMultiplier) {
+            multiplier * 2
+        } else {
+            multiplier
+        }
+    }
+
+    /// Whether the run should end for lack of lives,
+    /// which in versus mode is when either player runs out
+    pub fn out_of_lives(&self) -> bool {
+        if self.diff.versus {
+            self.players.iter().any(|player| player.lives == 0)
+        } else {
+            self.lives == 0
+        }
+    }
+
+    /// Index into [Simulation::players] of who is winning, [None] on a tie.
+    /// Running out of lives loses, otherwise the higher score wins
+    pub fn winner(&self) -> Option<usize> {
+        let rank = |player: &Player| (player.lives != 0 || !self.diff.versus, r32(player.score));
+        let best = self.players.iter().map(rank).max()?;
+        let mut leaders = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|(_index, player)| rank(player) == best);
+        match (leaders.next(), leaders.next()) {
+            (Some((index, _player)), None) => Some(index),
+            _ => None,
+        }
+    }
+
+    pub fn ride_speed(&self) -> f32 {
+        match self.diff.endless {
+            true => self.config.endless.ride_speed.at(self.time),
+            false => self.config.ride_speed,
+        }
+    }
+
+    pub fn distance_between_mailboxes(&self) -> f32 {
+        match self.diff.endless {
+            true => self.config.endless.distance_between_mailboxes.at(self.time),
+            false => self.config.distance_between_mailboxes,
+        }
+    }
+
+    pub fn double_mailbox_probability(&self) -> f64 {
+        match self.diff.endless {
+            true => self.config.endless.double_mailbox_probability.at(self.time) as f64,
+            false => self.config.double_mailbox_probability,
+        }
+    }
+
+    pub fn gravity(&self) -> f32 {
+        match self.diff.endless {
+            true => self.config.endless.gravity.at(self.time),
+            false => self.config.gravity,
+        }
+    }
+
+    pub fn effect_active(&self, kind: PowerUpKind) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    fn road_pos(&self, x: f32, latitude: f32) -> vec3<f32> {
+        let circle_pos = vec2(self.config.earth_radius, 0.0).rotate(Angle::from_radians(latitude));
+        vec3(x, circle_pos.x, -circle_pos.y)
+    }
+
+    pub fn mailbox_pos(&self, mailbox: &Mailbox) -> vec3<f32> {
+        self.road_pos(mailbox.x, mailbox.latitude)
+    }
+
+    pub fn power_up_pos(&self, power_up: &PowerUp) -> vec3<f32> {
+        self.road_pos(power_up.x, power_up.latitude)
+    }
+
+    /// Gameplay hit testing is done on a virtual screen instead of the real framebuffer
+    /// so that it does not depend on window size (which matters for replays)
+    pub fn virtual_framebuffer_size(&self) -> vec2<f32> {
+        vec2(VIRTUAL_SCREEN_ASPECT, 1.0) * self.camera.fov()
+    }
+    pub fn world_to_virtual_screen(&self, pos: vec2<f32>) -> vec2<f32> {
+        pos + self.virtual_framebuffer_size() / 2.0
+    }
+
+    pub fn hovered_mailbox(&self, cursor: vec2<f32>) -> Option<usize> {
+        let hand_radius = if self.effect_active(PowerUpKind::Magnet) {
+            self.config.magnet_hand_radius
+        } else {
+            self.config.hand_radius
+        };
+        self.mailboxes.iter().position(|mailbox| {
+            !self.throw_blocked(mailbox)
+                && self.aims_at(
+                    cursor,
+                    self.mailbox_pos(mailbox),
+                    self.config.mailbox_size,
+                    hand_radius,
+                )
+        })
+    }
+
+    /// Whether a hazard stands between the bike and the mailbox
+    fn throw_blocked(&self, mailbox: &Mailbox) -> bool {
+        self.hazards.iter().any(|hazard| {
+            self.config.hazards[hazard.kind].blocks_throws
+                && hazard.x.signum() == mailbox.x.signum()
+                && hazard.latitude > self.latitude
+                && hazard.latitude < mailbox.latitude
+        })
+    }
+
+    pub fn hazard_pos(&self, hazard: &Hazard) -> vec3<f32> {
+        self.road_pos(hazard.x, hazard.latitude)
+    }
+
+    pub fn hovered_power_up(&self, cursor: vec2<f32>) -> Option<usize> {
+        self.power_ups.iter().position(|power_up| {
+            self.aims_at(
+                cursor,
+                self.power_up_pos(power_up),
+                self.config.power_up_size,
+                self.config.hand_radius,
+            )
+        })
+    }
+
+    /// Cursor position aiming right at the center of the mailbox, [None] if it is not on screen
+    pub fn mailbox_aim(&self, mailbox: &Mailbox) -> Option<vec2<f32>> {
+        let framebuffer_size = self.virtual_framebuffer_size();
+        let up = vec3::cross(self.camera.dir(), vec3(1.0, 0.0, 0.0)).normalize_or_zero();
+        let center = self.mailbox_pos(mailbox) + up * self.config.mailbox_size / 2.0;
+        let center = self.camera.world_to_screen(framebuffer_size, center)?;
+        Some(center - framebuffer_size / 2.0)
+    }
+
+    /// Whether the cursor is within [ComboConfig::perfect_radius] of the mailbox center
+    pub fn perfect_aim(&self, cursor: vec2<f32>, mailbox: &Mailbox) -> bool {
+        self.aim_offset(cursor, self.mailbox_pos(mailbox), self.config.mailbox_size)
+            .is_some_and(|(offset, size)| offset.len() < size * self.config.combo.perfect_radius)
+    }
+
+    /// Whether the cursor is over something of the given size standing on the road at `pos`
+    fn aims_at(&self, cursor: vec2<f32>, pos: vec3<f32>, size: f32, hand_radius: f32) -> bool {
+        self.aim_offset(cursor, pos, size)
+            .is_some_and(|(offset, size)| {
+                // Virtual screen pixels are world units
+                Aabb2::point(vec2::ZERO)
+                    .extend_uniform(size)
+                    .extend_uniform(hand_radius)
+                    .contains(offset)
+            })
+    }
+
+    /// Cursor position relative to the center of something of the given size standing
+    /// on the road at `pos` and its half size, both on the virtual screen.
+    /// [None] if it is out of reach
+    fn aim_offset(&self, cursor: vec2<f32>, pos: vec3<f32>, size: f32) -> Option<(vec2<f32>, f32)> {
+        let framebuffer_size = self.virtual_framebuffer_size();
+        let cursor = self.world_to_virtual_screen(cursor);
+        let ray = self.camera.pixel_ray(framebuffer_size, cursor);
+        let camera_dir = self.camera.dir();
+        let right = vec3(1.0, 0.0, 0.0);
+        let up = vec3::cross(camera_dir, right).normalize_or_zero();
+
+        // dot(ray.from + ray.dir * t - pos, camera_dir) = 0
+        let t = vec3::dot(pos - ray.from, camera_dir) / vec3::dot(ray.dir, camera_dir);
+        if t < 0.0 || t * ray.dir.len() > self.config.max_throw_distance {
+            return None;
+        }
+
+        // let p = vec2(vec3::dot(p - pos, right), vec3::dot(p - pos, up));
+        // Aabb2::ZERO
+        //     .extend_symmetric(vec2(self.config.mailbox_size / 2.0, 0.0))
+        //     .extend_up(self.config.mailbox_size)
+        //     .extend_uniform(self.config.hand_radius)
+        //     .contains(p)
+
+        let center = pos + up * size / 2.0;
+        let center = self.camera.world_to_screen(framebuffer_size, center)?;
+        let pos = self.camera.world_to_screen(framebuffer_size, pos)?;
+        Some((cursor - center, (center - pos).len()))
+    }
+}
@@ -0,0 +1,1250 @@
+use super::*;
+
+use crate::testing::*;
+
+/// [simulation] where items only go into the mailboxes listed for them
+fn restricted_simulation(diff: Difficulty) -> Simulation {
+    let mut config = Config::clone(&config());
+    config.restricted_items = true;
+    simulation_with_config(config, diff)
+}
+
+fn delta_time(simulation: &Simulation) -> f32 {
+    1.0 / simulation.config.simulation_fps
+}
+
+/// Run for the given amount of time, returning all the events that happened
+fn run(simulation: &mut Simulation, time: f32) -> Vec<Event> {
+    let delta_time = delta_time(simulation);
+    let mut events = Vec::new();
+    for _ in 0..(time / delta_time).ceil() as usize {
+        simulation.update(delta_time);
+        events.extend(simulation.take_events());
+    }
+    events
+}
+
+fn touch(simulation: &mut Simulation, kind: TouchKind, position: vec2<f32>) -> Vec<Event> {
+    simulation.input(TouchInput {
+        kind,
+        id: None,
+        position,
+        player: 0,
+    });
+    simulation.take_events()
+}
+
+/// Pick a new item from the bag and toss it up
+fn juggle_new_item(simulation: &mut Simulation) {
+    let bag = simulation.players[0].bag_position.center();
+    assert_eq!(touch(simulation, TouchKind::Start, bag), [Event::Picked]);
+    assert_eq!(touch(simulation, TouchKind::End, bag), [Event::Juggled]);
+}
+
+fn throw_item(simulation: &mut Simulation, to_id: Id) {
+    throw_colored_item(simulation, to_id, None);
+}
+
+fn throw_colored_item(simulation: &mut Simulation, to_id: Id, mailbox_color: Option<usize>) {
+    throw_item_of_kind(simulation, to_id, 0, mailbox_color);
+}
+
+fn throw_item_of_kind(
+    simulation: &mut Simulation,
+    to_id: Id,
+    kind: usize,
+    mailbox_color: Option<usize>,
+) {
+    let mut item = Item::new(kind, 1.0, 1.0, &mut simulation.rng);
+    item.mailbox_color = mailbox_color;
+    simulation.thrown_items.push(ThrownItem {
+        item,
+        from: vec3::ZERO,
+        to: vec3::ZERO,
+        t: 0.0,
+        prev_t: 0.0,
+        to_id,
+        perfect: false,
+    });
+}
+
+fn assert_score_gain(simulation: &mut Simulation, expected: f32) {
+    let score = simulation.score;
+    simulation.update(delta_time(simulation));
+    let gained = simulation.score - score;
+    assert!(
+        (gained - expected).abs() < 1e-3,
+        "gained {gained}, expected {expected}",
+    );
+}
+
+#[test]
+fn passive_score_is_multiplied_by_items_in_play() {
+    let mut simulation = simulation(diff());
+    let per_step = delta_time(&simulation) * simulation.config.juggling_score_multiplier;
+
+    assert_eq!(simulation.multiplier(None), 1);
+    assert_score_gain(&mut simulation, per_step);
+
+    let bag = simulation.players[0].bag_position.center();
+    touch(&mut simulation, TouchKind::Start, bag);
+    assert_eq!(simulation.multiplier(None), 2);
+    assert_score_gain(&mut simulation, per_step * 2.0);
+
+    touch(&mut simulation, TouchKind::End, bag);
+    run(&mut simulation, 0.3);
+    juggle_new_item(&mut simulation);
+    assert_eq!(simulation.juggling_items.len(), 2);
+    assert_eq!(simulation.multiplier(None), 3);
+    assert_score_gain(&mut simulation, per_step * 3.0);
+}
+
+#[test]
+fn delivery_score_is_multiplied() {
+    let mut simulation = simulation(diff());
+    simulation.update(delta_time(&simulation));
+    simulation
+        .juggling_items
+        .push(Item::new(0, 1.0, 1.0, &mut simulation.rng));
+    simulation.juggling_items[0].pos = vec2(0.0, 100.0);
+    let mailbox_id = simulation.mailboxes[0].id;
+    throw_item(&mut simulation, mailbox_id);
+
+    let throw_time = simulation.config.throw_time;
+    let events = run(&mut simulation, throw_time + 0.1);
+    let delivered: Vec<f32> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Delivered { score, .. } => Some(*score),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(delivered, [simulation.config.items[0].score * 2.0]);
+    assert!(simulation
+        .mailboxes
+        .iter()
+        .all(|mailbox| mailbox.id != mailbox_id));
+    assert_eq!(simulation.lives, 3);
+}
+
+#[test]
+fn dropping_an_item_loses_a_life() {
+    let mut simulation = simulation(diff());
+    juggle_new_item(&mut simulation);
+
+    let events = run(&mut simulation, 5.0);
+    assert!(simulation.juggling_items.is_empty());
+    assert_eq!(simulation.lives, 2);
+    let dropped = events
+        .iter()
+        .position(|event| matches!(event, Event::Dropped { pos } if pos.y <= -5.0))
+        .expect("item should have dropped below the bag");
+    assert_eq!(events[dropped + 1], Event::LifeLost { lives_left: 2 });
+}
+
+#[test]
+fn item_above_the_bag_is_not_lost() {
+    let mut simulation = simulation(diff());
+    let mut item = Item::new(0, 1.0, 1.0, &mut simulation.rng);
+    item.pos = vec2(0.0, simulation.players[0].bag_position.min.y + 0.1);
+    item.vel = vec2(0.0, 10.0);
+    simulation.juggling_items.push(item);
+    simulation.update(delta_time(&simulation));
+    assert_eq!(simulation.juggling_items.len(), 1);
+    assert_eq!(simulation.lives, 3);
+}
+
+#[test]
+fn delivering_to_despawned_mailbox_loses_a_life() {
+    let mut simulation = simulation(diff());
+    simulation.update(delta_time(&simulation));
+    throw_item(&mut simulation, Id::MAX);
+
+    let throw_time = simulation.config.throw_time;
+    let events = run(&mut simulation, throw_time + 0.1);
+    assert!(simulation.thrown_items.is_empty());
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::Missed { .. })));
+    assert!(events.contains(&Event::LifeLost { lives_left: 2 }));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Event::Delivered { .. })));
+    assert_eq!(simulation.lives, 2);
+}
+
+/// Throw an item of the given color into the first mailbox, returning delivery events
+fn deliver_colored(simulation: &mut Simulation, matching: bool) -> Vec<Event> {
+    simulation.update(delta_time(simulation));
+    let mailbox = &simulation.mailboxes[0];
+    let colors = simulation.config.mailbox_colors.len();
+    let color = match matching {
+        true => mailbox.color,
+        false => (mailbox.color + 1) % colors,
+    };
+    let mailbox_id = mailbox.id;
+    throw_colored_item(simulation, mailbox_id, Some(color));
+    let throw_time = simulation.config.throw_time;
+    run(simulation, throw_time + 0.1)
+}
+
+#[test]
+fn color_matching_draws_colors_from_the_bag() {
+    let mut simulation = simulation(Difficulty {
+        color_matching: true,
+        ..diff()
+    });
+    let next_color = simulation.next_color.expect("next color should be shown");
+    juggle_new_item(&mut simulation);
+    assert_eq!(simulation.juggling_items[0].mailbox_color, Some(next_color));
+    assert!(simulation.next_color.is_some());
+
+    let mut simulation = self::simulation(diff());
+    assert_eq!(simulation.next_color, None);
+    juggle_new_item(&mut simulation);
+    assert_eq!(simulation.juggling_items[0].mailbox_color, None);
+}
+
+#[test]
+fn matching_color_scores_a_bonus() {
+    let mut simulation = simulation(diff());
+    let events = deliver_colored(&mut simulation, true);
+    let config = &simulation.config;
+    assert!(events.contains(&Event::Delivered {
+        pos: vec3::ZERO,
+        score: config.items[0].score + config.color_match_bonus,
+        color_match: Some(true),
+        streak: 1,
+        pair: false,
+        perfect: false,
+        player: 0,
+    }));
+    assert_eq!(simulation.lives, 3);
+}
+
+#[test]
+fn mismatched_color_loses_a_life() {
+    let mut simulation = simulation(diff());
+    assert_eq!(
+        simulation.config.color_mismatch_penalty,
+        ColorMismatchPenalty::Life,
+    );
+    let events = deliver_colored(&mut simulation, false);
+    assert!(events.contains(&Event::Delivered {
+        pos: vec3::ZERO,
+        score: simulation.config.items[0].score,
+        color_match: Some(false),
+        streak: 1,
+        pair: false,
+        perfect: false,
+        player: 0,
+    }));
+    assert!(events.contains(&Event::LifeLost { lives_left: 2 }));
+    assert_eq!(simulation.lives, 2);
+}
+
+fn kind_index(simulation: &Simulation, name: &str) -> usize {
+    simulation
+        .config
+        .items
+        .iter()
+        .position(|kind| kind.name == name)
+        .unwrap()
+}
+
+#[test]
+fn bag_draws_items_by_weight() {
+    let mut config = Config::clone(&config());
+    for (index, kind) in config.items.iter_mut().enumerate() {
+        kind.weight = if index == 1 { 1.0 } else { 0.0 };
+    }
+    let mut simulation = simulation_with_config(config, diff());
+    for _ in 0..5 {
+        assert_eq!(simulation.next_kind, 1);
+        assert_eq!(simulation.pull_item().kind, 1);
+    }
+}
+
+#[test]
+fn heavier_items_fall_faster() {
+    let mut simulation = simulation(diff());
+    let kinds = [
+        kind_index(&simulation, "newspaper"),
+        kind_index(&simulation, "parcel"),
+    ];
+    for kind in kinds {
+        let mut item = Item::new(kind, 1.0, 1.0, &mut simulation.rng);
+        item.pos = vec2(0.0, 100.0);
+        simulation.juggling_items.push(item);
+    }
+    run(&mut simulation, 0.5);
+    let [newspaper, parcel] = [0, 1].map(|index| simulation.juggling_items[index].vel.y);
+    let [newspaper_scale, parcel_scale] =
+        kinds.map(|kind| simulation.config.items[kind].gravity_scale);
+    assert!(parcel < newspaper);
+    assert!((parcel / newspaper - parcel_scale / newspaper_scale).abs() < 1e-3);
+}
+
+/// Color of a mailbox that does not list the item kind
+fn rejecting_color(simulation: &Simulation, kind: usize) -> usize {
+    let colors = simulation.config.mailbox_colors.len();
+    (0..colors)
+        .find(|&color| !simulation.config.items[kind].accepts(color))
+        .expect("some mailboxes should not accept the item")
+}
+
+#[test]
+fn rejected_item_loses_a_life_and_keeps_the_mailbox() {
+    let mut simulation = restricted_simulation(diff());
+    simulation.update(delta_time(&simulation));
+    let parcel = kind_index(&simulation, "parcel");
+    simulation.mailboxes[0].color = rejecting_color(&simulation, parcel);
+    let mailbox_id = simulation.mailboxes[0].id;
+    throw_item_of_kind(&mut simulation, mailbox_id, parcel, None);
+
+    let throw_time = simulation.config.throw_time;
+    let events = run(&mut simulation, throw_time + 0.1);
+    assert!(events.contains(&Event::Rejected { pos: vec3::ZERO }));
+    assert!(events.contains(&Event::LifeLost { lives_left: 2 }));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Event::Delivered { .. })));
+    assert!(simulation
+        .mailboxes
+        .iter()
+        .any(|mailbox| mailbox.id == mailbox_id));
+}
+
+#[test]
+fn items_go_into_any_mailbox_unless_restricted() {
+    let mut simulation = simulation(diff());
+    simulation.update(delta_time(&simulation));
+    let parcel = kind_index(&simulation, "parcel");
+    simulation.mailboxes[0].color = rejecting_color(&simulation, parcel);
+    let mailbox_id = simulation.mailboxes[0].id;
+    throw_item_of_kind(&mut simulation, mailbox_id, parcel, None);
+
+    let throw_time = simulation.config.throw_time;
+    let events = run(&mut simulation, throw_time + 0.1);
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::Delivered { .. })));
+    assert_eq!(simulation.lives, 3);
+}
+
+#[test]
+fn config_with_unknown_mailbox_colors_is_rejected() {
+    let mut config = Config::clone(&config());
+    config.validate().unwrap();
+    let colors = config.mailbox_colors.len();
+    config.items[0].mailboxes = vec![0, colors];
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn config_without_positive_weights_is_rejected() {
+    let mut config = Config::clone(&config());
+    for kind in &mut config.items {
+        kind.weight = 0.0;
+    }
+    assert!(config.validate().is_err());
+    config.items.clear();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn config_with_negative_power_up_weights_is_rejected() {
+    let mut config = Config::clone(&config());
+    config.power_ups[0].weight = -1.0;
+    assert!(config.validate().is_err());
+    config.power_ups.clear();
+    config.validate().unwrap();
+}
+
+#[test]
+fn item_kind_sets_delivery_score() {
+    let mut simulation = simulation(diff());
+    simulation.update(delta_time(&simulation));
+    let parcel = kind_index(&simulation, "parcel");
+    simulation.mailboxes[0].color = simulation.config.items[parcel].mailboxes[0];
+    let mailbox_id = simulation.mailboxes[0].id;
+    throw_item_of_kind(&mut simulation, mailbox_id, parcel, None);
+
+    let throw_time = simulation.config.throw_time;
+    let events = run(&mut simulation, throw_time + 0.1);
+    assert!(events.contains(&Event::Delivered {
+        pos: vec3::ZERO,
+        score: simulation.config.items[parcel].score,
+        color_match: None,
+        streak: 1,
+        pair: false,
+        perfect: false,
+        player: 0,
+    }));
+}
+
+#[test]
+fn mailboxes_and_houses_stay_within_spawn_window() {
+    let mut simulation = simulation(diff());
+    let config = simulation.config.clone();
+    let despawn = config.despawn_distance.to_radians();
+    let spawn = config.spawn_distance.to_radians();
+    const EPS: f32 = 1e-3;
+    let mut ids_seen = HashSet::new();
+    for _ in 0..(30.0 / delta_time(&simulation)) as usize {
+        simulation.update(delta_time(&simulation));
+        let latitude = simulation.latitude;
+        for mailbox in &simulation.mailboxes {
+            assert!(mailbox.latitude > latitude - despawn);
+            assert!(
+                mailbox.latitude
+                    <= latitude + spawn + config.distance_between_mailboxes.to_radians() + EPS
+            );
+            ids_seen.insert(mailbox.id);
+        }
+        assert!(simulation.mailboxes.last().unwrap().latitude >= latitude + spawn);
+        for house in &simulation.houses {
+            assert!(house.latitude > latitude - despawn);
+            assert!(
+                house.latitude
+                    <= latitude + spawn + config.distance_between_houses.to_radians() + EPS
+            );
+            assert!(house.texture < 3);
+        }
+        assert!(simulation.houses.last().unwrap().latitude >= latitude + spawn);
+    }
+    // Mailboxes were despawned behind us and new ones spawned in front
+    assert!(simulation.mailboxes.iter().all(|mailbox| mailbox.id != 0));
+    assert!(ids_seen.len() > simulation.mailboxes.len());
+}
+
+#[test]
+fn game_ends_when_time_is_up() {
+    let mut simulation = simulation(Difficulty {
+        game_time: 1.0,
+        ..diff()
+    });
+    let bag = simulation.players[0].bag_position.center();
+    touch(&mut simulation, TouchKind::Start, bag);
+
+    let events = run(&mut simulation, 1.1);
+    assert!(simulation.game_over);
+    assert!(events.contains(&Event::GameOver {
+        reason: GameOverReason::TimeUp,
+    }));
+    // Held item got released when the game ended
+    assert!(events.contains(&Event::Juggled));
+    assert!(simulation
+        .touches
+        .iter()
+        .all(|touch| touch.holding.is_none()));
+
+    // No more score or input after the end
+    let score = simulation.score;
+    assert!(touch(&mut simulation, TouchKind::Start, bag).is_empty());
+    let events = run(&mut simulation, 0.5);
+    assert_eq!(simulation.score, score);
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Event::GameOver { .. })));
+}
+
+#[test]
+fn game_ends_when_out_of_lives() {
+    let mut simulation = simulation(Difficulty { lives: 1, ..diff() });
+    juggle_new_item(&mut simulation);
+
+    let events = run(&mut simulation, 5.0);
+    assert_eq!(simulation.lives, 0);
+    assert!(simulation.game_over);
+    let life_lost = events
+        .iter()
+        .position(|event| *event == Event::LifeLost { lives_left: 0 })
+        .unwrap();
+    let game_over = events
+        .iter()
+        .position(|event| {
+            *event
+                == Event::GameOver {
+                    reason: GameOverReason::OutOfLives,
+                }
+        })
+        .unwrap();
+    assert!(life_lost < game_over);
+}
+
+#[test]
+fn same_seed_and_inputs_give_same_run() {
+    let play = || {
+        let mut simulation = simulation(diff());
+        for _ in 0..3 {
+            juggle_new_item(&mut simulation);
+            run(&mut simulation, 0.3);
+        }
+        run(&mut simulation, 5.0);
+        (
+            simulation.score,
+            simulation.lives,
+            simulation
+                .mailboxes
+                .iter()
+                .map(|mailbox| (mailbox.x, mailbox.latitude, mailbox.color))
+                .collect::<Vec<_>>(),
+        )
+    };
+    assert_eq!(play(), play());
+}
+
+#[test]
+fn power_ups_stay_within_spawn_window() {
+    let mut simulation = simulation(diff());
+    let config = simulation.config.clone();
+    let despawn = config.despawn_distance.to_radians();
+    let spawn = config.spawn_distance.to_radians();
+    const EPS: f32 = 1e-3;
+    let first_id = {
+        simulation.update(delta_time(&simulation));
+        simulation.power_ups[0].id
+    };
+    for _ in 0..(30.0 / delta_time(&simulation)) as usize {
+        simulation.update(delta_time(&simulation));
+        let latitude = simulation.latitude;
+        for power_up in &simulation.power_ups {
+            assert!(power_up.latitude > latitude - despawn);
+            assert!(
+                power_up.latitude
+                    <= latitude + spawn + config.distance_between_power_ups.to_radians() + EPS
+            );
+            assert!(power_up.x.abs() <= config.road_width / 2.0);
+        }
+        assert!(simulation.power_ups.last().unwrap().latitude >= latitude + spawn);
+    }
+    assert!(simulation
+        .power_ups
+        .iter()
+        .all(|power_up| power_up.id != first_id));
+}
+
+/// Puts a power-up of the given kind on the road and hits it with a thrown item
+fn hit_power_up(simulation: &mut Simulation, kind: PowerUpKind, duration: f32) -> Vec<Event> {
+    let id = simulation.next_id;
+    simulation.next_id += 1;
+    simulation.power_ups.push(PowerUp {
+        id,
+        x: 0.0,
+        latitude: simulation.latitude + 0.1,
+        kind,
+        duration,
+    });
+    throw_item(simulation, id);
+    let throw_time = simulation.config.throw_time;
+    run(simulation, throw_time + 0.1)
+}
+
+#[test]
+fn hitting_a_power_up_starts_its_effect() {
+    let mut simulation = simulation(diff());
+    let events = hit_power_up(&mut simulation, PowerUpKind::DoubleMultiplier, 1.0);
+    assert!(events.contains(&Event::PowerUpHit {
+        pos: vec3::ZERO,
+        kind: PowerUpKind::DoubleMultiplier,
+    }));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Event::LifeLost { .. } | Event::Missed { .. })));
+    assert!(simulation.effect_active(PowerUpKind::DoubleMultiplier));
+    assert_eq!(simulation.multiplier(None), 2);
+
+    run(&mut simulation, 1.0);
+    assert!(!simulation.effect_active(PowerUpKind::DoubleMultiplier));
+    assert_eq!(simulation.multiplier(None), 1);
+}
+
+#[test]
+fn extra_life_restores_a_lost_life() {
+    let mut simulation = simulation(diff());
+    hit_power_up(&mut simulation, PowerUpKind::ExtraLife, 0.0);
+    assert_eq!(simulation.lives, 3);
+    assert!(simulation.effects.is_empty());
+
+    simulation.lives = 1;
+    hit_power_up(&mut simulation, PowerUpKind::ExtraLife, 0.0);
+    assert_eq!(simulation.lives, 2);
+}
+
+#[test]
+fn slow_motion_slows_the_ride() {
+    let mut simulation = simulation(diff());
+    hit_power_up(&mut simulation, PowerUpKind::SlowMotion, 10.0);
+    let latitude = simulation.latitude;
+    simulation.update(delta_time(&simulation));
+    let expected = simulation.config.ride_speed
+        * simulation.config.slow_motion_time_scale
+        * delta_time(&simulation);
+    assert!((simulation.latitude - latitude - expected).abs() < 1e-6);
+}
+
+#[test]
+fn hazard_spawn_chance_ramps_over_the_run() {
+    let config = config();
+    let kind = &config.hazards[0];
+    let ramp_time = config.hazard_ramp_time;
+    assert_eq!(kind.spawn_chance(0.0, ramp_time), kind.spawn_chance);
+    assert!(
+        (kind.spawn_chance(ramp_time / 2.0, ramp_time)
+            - (kind.spawn_chance + kind.max_spawn_chance) / 2.0)
+            .abs()
+            < 1e-6
+    );
+    assert_eq!(
+        kind.spawn_chance(ramp_time, ramp_time),
+        kind.max_spawn_chance
+    );
+    assert_eq!(
+        kind.spawn_chance(ramp_time * 2.0, ramp_time),
+        kind.max_spawn_chance
+    );
+}
+
+#[test]
+fn hazards_spawn_by_chance() {
+    for chance in [0.0, 1.0] {
+        let mut config = Config::clone(&config());
+        for kind in &mut config.hazards {
+            kind.spawn_chance = chance;
+            kind.max_spawn_chance = chance;
+        }
+        let hazard_count = config.hazards.len();
+        let mut simulation = simulation_with_config(config, diff());
+        run(&mut simulation, 5.0);
+        if chance == 0.0 {
+            assert!(simulation.hazards.is_empty());
+        } else {
+            assert!(simulation.hazards.len() >= hazard_count);
+        }
+    }
+}
+
+/// Puts a hazard of the given kind right in front of the bike
+fn place_hazard(simulation: &mut Simulation, kind: usize, x: f32, latitude: f32) {
+    simulation.hazards.push(Hazard {
+        kind,
+        x,
+        latitude,
+        passed: false,
+    });
+}
+
+#[test]
+fn riding_over_a_hazard_knocks_juggled_items() {
+    let mut config = Config::clone(&config());
+    config.hazards[0].knock = 5.0;
+    config.hazards[0].speed = 0.0;
+    let mut simulation = simulation_with_config(config, diff());
+    let mut item = Item::new(0, 1.0, 1.0, &mut simulation.rng);
+    item.pos = vec2(0.0, 100.0);
+    simulation.juggling_items.push(item);
+    let latitude = simulation.latitude + 0.01;
+    place_hazard(&mut simulation, 0, 0.0, latitude);
+
+    let events = run(&mut simulation, 0.5);
+    assert_eq!(
+        events
+            .iter()
+            .filter(|event| **event == Event::HazardHit { kind: 0 })
+            .count(),
+        1
+    );
+    assert_ne!(simulation.juggling_items[0].vel.x, 0.0);
+}
+
+#[test]
+fn riding_past_a_hazard_does_not_knock_juggled_items() {
+    let mut config = Config::clone(&config());
+    config.hazards[0].knock = 5.0;
+    config.hazards[0].speed = 0.0;
+    let beside = (config.hazards[0].size + config.bike_width) / 2.0 + 0.1;
+    let mut simulation = simulation_with_config(config, diff());
+    let mut item = Item::new(0, 1.0, 1.0, &mut simulation.rng);
+    item.pos = vec2(0.0, 100.0);
+    simulation.juggling_items.push(item);
+    let latitude = simulation.latitude + 0.01;
+    place_hazard(&mut simulation, 0, beside, latitude);
+    place_hazard(&mut simulation, 0, -beside, latitude);
+
+    let events = run(&mut simulation, 0.5);
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Event::HazardHit { .. })));
+    assert!(simulation.hazards[..2].iter().all(|hazard| hazard.passed));
+    assert_eq!(simulation.juggling_items[0].vel.x, 0.0);
+}
+
+#[test]
+fn blocking_hazard_stops_throws_behind_it() {
+    let mut simulation = simulation(diff());
+    simulation.update(delta_time(&simulation));
+    let dog = kind_index_of_hazard(&simulation, "dog");
+    let mailbox = &simulation.mailboxes[0];
+    let (side, mailbox_latitude) = (mailbox.x.signum(), mailbox.latitude);
+    let between = (simulation.latitude + mailbox_latitude) / 2.0;
+
+    place_hazard(&mut simulation, dog, -side, between);
+    assert!(!simulation.throw_blocked(&simulation.mailboxes[0]));
+
+    place_hazard(&mut simulation, dog, side, mailbox_latitude + 0.1);
+    assert!(!simulation.throw_blocked(&simulation.mailboxes[0]));
+
+    place_hazard(&mut simulation, dog, side, between);
+    assert!(simulation.throw_blocked(&simulation.mailboxes[0]));
+}
+
+fn kind_index_of_hazard(simulation: &Simulation, name: &str) -> usize {
+    simulation
+        .config
+        .hazards
+        .iter()
+        .position(|kind| kind.name == name)
+        .unwrap()
+}
+
+#[test]
+fn curve_interpolates_between_points() {
+    let curve: Curve = toml::from_str::<HashMap<String, Curve>>("c = [[0, 1], [10, 2], [20, 4]]")
+        .unwrap()
+        .remove("c")
+        .unwrap();
+    assert_eq!(curve.at(-5.0), 1.0);
+    assert_eq!(curve.at(0.0), 1.0);
+    assert_eq!(curve.at(5.0), 1.5);
+    assert_eq!(curve.at(10.0), 2.0);
+    assert_eq!(curve.at(15.0), 3.0);
+    assert_eq!(curve.at(100.0), 4.0);
+}
+
+#[test]
+fn endless_mode_only_ends_when_out_of_lives() {
+    let mut simulation = simulation(Difficulty {
+        endless: true,
+        game_time: 0.0,
+        lives: 1,
+        ..diff()
+    });
+    let events = run(&mut simulation, 5.0);
+    assert!(!simulation.game_over);
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Event::GameOver { .. })));
+
+    juggle_new_item(&mut simulation);
+    let events = run(&mut simulation, 5.0);
+    assert!(simulation.game_over);
+    assert!(events.contains(&Event::GameOver {
+        reason: GameOverReason::OutOfLives
+    }));
+}
+
+#[test]
+fn endless_mode_ramps_along_curves() {
+    let mut simulation = simulation(Difficulty {
+        endless: true,
+        game_time: 0.0,
+        ..diff()
+    });
+    let endless = simulation.config.endless.clone();
+    assert_eq!(simulation.ride_speed(), endless.ride_speed.at(0.0));
+    assert_eq!(simulation.gravity(), endless.gravity.at(0.0));
+    run(&mut simulation, 60.0);
+    let time = simulation.time;
+    assert_eq!(simulation.ride_speed(), endless.ride_speed.at(time));
+    assert_eq!(
+        simulation.distance_between_mailboxes(),
+        endless.distance_between_mailboxes.at(time)
+    );
+    assert!(simulation.ride_speed() > endless.ride_speed.at(0.0));
+
+    // Constant values from config outside of endless mode
+    let simulation = self::simulation(diff());
+    assert_eq!(simulation.ride_speed(), simulation.config.ride_speed);
+}
+
+#[test]
+fn mode_flags_are_only_serialized_when_on() {
+    let classic = serde_json::to_string(&diff()).unwrap();
+    assert_eq!(classic, r#"{"time_scale":1.0,"game_time":60.0,"lives":3}"#);
+    let endless = serde_json::to_string(&Difficulty {
+        endless: true,
+        game_time: 0.0,
+        ..diff()
+    })
+    .unwrap();
+    assert!(endless.contains(r#""endless":true"#));
+    assert_eq!(
+        serde_json::from_str::<Difficulty>(&classic).unwrap(),
+        diff()
+    );
+}
+
+#[test]
+fn scores_of_new_rules_get_their_own_category() {
+    let diff = Difficulty::new(&config(), 0, 0, 0);
+    assert_eq!(diff.rules, Difficulty::RULES);
+    let meta = serde_json::to_string(&diff).unwrap();
+    assert!(meta.contains(&format!(r#""rules":{}"#, Difficulty::RULES)));
+    // Scores made before the rules had versions
+    let old = Difficulty {
+        rules: 0,
+        ..diff.clone()
+    };
+    let old_meta = serde_json::to_string(&old).unwrap();
+    assert!(!old_meta.contains("rules"));
+    assert_ne!(serde_json::from_str::<Difficulty>(&old_meta).unwrap(), diff);
+}
+
+#[test]
+fn practice_mode_never_loses_lives_or_runs_out_of_time() {
+    let mut simulation = simulation(Difficulty {
+        practice: true,
+        ..diff()
+    });
+    juggle_new_item(&mut simulation);
+    let events = run(&mut simulation, 70.0);
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::Dropped { .. })));
+    assert!(events.contains(&Event::LifeSpared));
+    assert!(!events
+        .iter()
+        .any(|event| matches!(event, Event::LifeLost { .. } | Event::GameOver { .. })));
+    assert_eq!(simulation.lives, 3);
+    assert!(!simulation.game_over);
+}
+
+#[test]
+fn practice_mode_counts_stats() {
+    let mut simulation = simulation(Difficulty {
+        practice: true,
+        ..diff()
+    });
+    juggle_new_item(&mut simulation);
+    juggle_new_item(&mut simulation);
+    assert_eq!(simulation.stats.juggle_streak, 2);
+    assert_eq!(simulation.stats.accuracy(), None);
+    run(&mut simulation, 5.0);
+    assert_eq!(simulation.stats.drops, 2);
+    assert_eq!(simulation.stats.juggle_streak, 0);
+    assert_eq!(simulation.stats.longest_juggle_streak, 2);
+
+    throw_item(&mut simulation, Id::MAX);
+    let events = deliver_colored(&mut simulation, true);
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::Delivered { .. })));
+    assert_eq!(simulation.stats.deliveries, 1);
+    assert_eq!(simulation.stats.misses, 1);
+    assert_eq!(simulation.stats.accuracy(), Some(0.5));
+}
+
+#[test]
+fn road_does_not_depend_on_how_the_player_plays() {
+    let mut idle = simulation(diff());
+    let mut juggling = simulation(diff());
+    juggle_new_item(&mut juggling);
+    juggle_new_item(&mut juggling);
+    run(&mut idle, 10.0);
+    run(&mut juggling, 10.0);
+    let road = |simulation: &Simulation| {
+        simulation
+            .mailboxes
+            .iter()
+            .map(|mailbox| (mailbox.id, mailbox.x, mailbox.latitude, mailbox.color))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(road(&idle), road(&juggling));
+}
+
+/// Deliver an item to the first mailbox, returning the delivery event
+fn deliver_to_first_mailbox(simulation: &mut Simulation, perfect: bool) -> Event {
+    let mailbox_id = simulation.mailboxes[0].id;
+    throw_item(simulation, mailbox_id);
+    simulation.thrown_items.last_mut().unwrap().perfect = perfect;
+    let throw_time = simulation.config.throw_time;
+    run(simulation, throw_time + 0.1)
+        .into_iter()
+        .find(|event| matches!(event, Event::Delivered { .. }))
+        .expect("item should be delivered")
+}
+
+fn delivered_score(event: &Event) -> f32 {
+    match event {
+        Event::Delivered { score, .. } => *score,
+        _ => unreachable!(),
+    }
+}
+
+/// Pair bonus would get in the way when the first mailboxes happen to be a pair
+fn simulation_without_pair_bonus() -> Simulation {
+    let mut config = Config::clone(&config());
+    config.combo.pair_time = 0.0;
+    let mut simulation = simulation_with_config(config, diff());
+    simulation.update(delta_time(&simulation));
+    simulation
+}
+
+#[test]
+fn delivery_streak_builds_a_multiplier() {
+    let mut simulation = simulation_without_pair_bonus();
+    let combo = simulation.config.combo.clone();
+    let score = simulation.config.items[0].score;
+    assert_eq!(combo.streak_multiplier(1), 1);
+    assert_eq!(combo.streak_multiplier(combo.deliveries_per_level), 2);
+    assert_eq!(combo.streak_multiplier(1000), combo.max_streak_multiplier);
+    for streak in 1..=combo.deliveries_per_level {
+        let event = deliver_to_first_mailbox(&mut simulation, false);
+        assert_eq!(
+            delivered_score(&event),
+            score * combo.streak_multiplier(streak) as f32
+        );
+    }
+    assert_eq!(
+        simulation.players[0].delivery_streak,
+        combo.deliveries_per_level
+    );
+
+    throw_item(&mut simulation, Id::MAX);
+    let throw_time = simulation.config.throw_time;
+    run(&mut simulation, throw_time + 0.1);
+    assert_eq!(simulation.players[0].delivery_streak, 0);
+}
+
+#[test]
+fn delivery_streak_decays_over_time() {
+    let mut simulation = simulation_without_pair_bonus();
+    deliver_to_first_mailbox(&mut simulation, false);
+    deliver_to_first_mailbox(&mut simulation, false);
+    assert_eq!(simulation.players[0].delivery_streak, 2);
+    let decay_time = simulation.config.combo.decay_time;
+    run(&mut simulation, decay_time - 0.5);
+    assert_eq!(simulation.players[0].delivery_streak, 2);
+    run(&mut simulation, 1.0);
+    assert_eq!(simulation.players[0].delivery_streak, 1);
+    run(&mut simulation, decay_time);
+    assert_eq!(simulation.players[0].delivery_streak, 0);
+}
+
+#[test]
+fn delivering_to_both_mailboxes_of_a_pair_scores_a_bonus() {
+    let mut simulation = simulation(diff());
+    simulation.update(delta_time(&simulation));
+    let latitude = simulation.mailboxes[0].latitude;
+    simulation.mailboxes[1].latitude = latitude;
+    let config = simulation.config.clone();
+    let score = config.items[0].score;
+
+    let first = deliver_to_first_mailbox(&mut simulation, false);
+    assert!(matches!(first, Event::Delivered { pair: false, .. }));
+    let second = deliver_to_first_mailbox(&mut simulation, false);
+    assert!(matches!(second, Event::Delivered { pair: true, .. }));
+    assert_eq!(
+        delivered_score(&second),
+        (score + config.combo.pair_bonus) * config.combo.streak_multiplier(2) as f32
+    );
+}
+
+#[test]
+fn perfect_hits_score_a_bonus() {
+    let mut simulation = simulation_without_pair_bonus();
+    let event = deliver_to_first_mailbox(&mut simulation, true);
+    let config = simulation.config.clone();
+    assert!(matches!(event, Event::Delivered { perfect: true, .. }));
+    assert_eq!(
+        delivered_score(&event),
+        config.items[0].score + config.combo.perfect_bonus
+    );
+}
+
+#[test]
+fn perfect_aim_is_near_the_mailbox_center() {
+    let mut simulation = simulation(diff());
+    simulation.update(delta_time(&simulation));
+    simulation.mailboxes[0].latitude = simulation.latitude + 20f32.to_radians();
+    let mailbox = &simulation.mailboxes[0];
+    let (offset, size) = simulation
+        .aim_offset(
+            vec2::ZERO,
+            simulation.mailbox_pos(mailbox),
+            simulation.config.mailbox_size,
+        )
+        .expect("mailbox should be in reach");
+    // Offset of the center from the middle of the screen
+    let center = -offset;
+    assert!(simulation.perfect_aim(center, mailbox));
+    let off_center = center + vec2(size * 0.9, 0.0);
+    assert!(!simulation.perfect_aim(off_center, mailbox));
+    assert_eq!(simulation.hovered_mailbox(off_center), Some(0));
+}
+
+fn player_touch(
+    simulation: &mut Simulation,
+    kind: TouchKind,
+    id: u64,
+    player: usize,
+    position: vec2<f32>,
+) -> Vec<Event> {
+    simulation.input(TouchInput {
+        kind,
+        id: Some(id),
+        position,
+        player,
+    });
+    simulation.take_events()
+}
+
+#[test]
+fn coop_players_pick_from_their_own_bags() {
+    let mut simulation = simulation(Difficulty {
+        coop: true,
+        ..diff()
+    });
+    assert_eq!(simulation.players.len(), 2);
+    let bags = simulation
+        .players
+        .iter()
+        .map(|player| player.bag_position)
+        .collect::<Vec<_>>();
+    assert!(!bags[0].intersects(&bags[1]));
+
+    let events = player_touch(&mut simulation, TouchKind::Start, 1, 1, bags[0].center());
+    assert_eq!(events, [Event::PickFailed]);
+    player_touch(&mut simulation, TouchKind::End, 1, 1, bags[0].center());
+
+    let events = player_touch(&mut simulation, TouchKind::Start, 2, 1, bags[1].center());
+    assert_eq!(events, [Event::Picked]);
+    let events = player_touch(&mut simulation, TouchKind::End, 2, 1, bags[1].center());
+    assert_eq!(events, [Event::Juggled]);
+    assert_eq!(simulation.juggling_items[0].player, 1);
+}
+
+#[test]
+fn coop_deliveries_score_for_the_thrower_and_juggling_is_shared() {
+    let mut config = Config::clone(&config());
+    config.combo.pair_time = 0.0;
+    let mut simulation = simulation_with_config(
+        config,
+        Difficulty {
+            coop: true,
+            ..diff()
+        },
+    );
+    simulation.update(delta_time(&simulation));
+
+    let mailbox_id = simulation.mailboxes[0].id;
+    throw_item(&mut simulation, mailbox_id);
+    simulation.thrown_items.last_mut().unwrap().item.player = 1;
+    let throw_time = simulation.config.throw_time;
+    let delivered = run(&mut simulation, throw_time + 0.1)
+        .into_iter()
+        .find(|event| matches!(event, Event::Delivered { .. }))
+        .expect("item should be delivered");
+
+    let first = &simulation.players[0];
+    let second = &simulation.players[1];
+    assert!(first.score > 0.0, "juggling score should be shared");
+    assert!((second.score - first.score - delivered_score(&delivered)).abs() < 1e-3);
+    assert!((first.score + second.score - simulation.score).abs() < 1e-3);
+}
+
+fn throw_item_as(simulation: &mut Simulation, to_id: Id, player: usize) {
+    throw_item(simulation, to_id);
+    simulation.thrown_items.last_mut().unwrap().item.player = player;
+}
+
+#[test]
+fn versus_first_item_to_land_claims_the_mailbox() {
+    let mut simulation = simulation(Difficulty {
+        versus: true,
+        ..diff()
+    });
+    simulation.update(delta_time(&simulation));
+    let mailbox_id = simulation.mailboxes[0].id;
+
+    throw_item_as(&mut simulation, mailbox_id, 0);
+    run(&mut simulation, 0.1);
+    throw_item_as(&mut simulation, mailbox_id, 1);
+    let throw_time = simulation.config.throw_time;
+    let events = run(&mut simulation, throw_time + 0.1);
+
+    let landed = events
+        .iter()
+        .filter(|event| matches!(event, Event::Delivered { .. } | Event::Missed { .. }))
+        .collect::<Vec<_>>();
+    assert!(matches!(
+        landed[..],
+        [Event::Delivered { .. }, Event::Missed { .. }]
+    ));
+    assert!(events.contains(&Event::LifeLost { lives_left: 2 }));
+    assert_eq!(simulation.players[0].lives, 3);
+    assert_eq!(simulation.players[1].lives, 2);
+    assert_eq!(simulation.players[0].stats.deliveries, 1);
+    assert_eq!(simulation.players[1].stats.misses, 1);
+}
+
+#[test]
+fn versus_rival_items_get_knocked_out_of_the_air() {
+    let mut simulation = simulation(Difficulty {
+        versus: true,
+        ..diff()
+    });
+    let bag = simulation.players[1].bag_position.center();
+    player_touch(&mut simulation, TouchKind::Start, 1, 1, bag);
+    player_touch(&mut simulation, TouchKind::End, 1, 1, bag);
+    let pos = simulation.juggling_items[0].pos;
+
+    let events = player_touch(&mut simulation, TouchKind::Start, 2, 0, pos);
+    assert_eq!(events, [Event::Knocked { pos }]);
+    assert!(simulation
+        .touches
+        .iter()
+        .all(|touch| touch.holding.is_none()));
+    assert!(simulation.juggling_items[0].vel.y < 0.0);
+
+    let events = run(&mut simulation, 1.0);
+    assert!(events
+        .iter()
+        .any(|event| matches!(event, Event::Dropped { .. })));
+    assert_eq!(simulation.players[0].lives, 3);
+    assert_eq!(simulation.players[1].lives, 2);
+    assert_eq!(simulation.players[1].stats.drops, 1);
+}
+
+#[test]
+fn versus_ends_when_either_player_runs_out_of_lives() {
+    let mut simulation = simulation(Difficulty {
+        versus: true,
+        lives: 1,
+        ..diff()
+    });
+    let bag = simulation.players[1].bag_position.center();
+    player_touch(&mut simulation, TouchKind::Start, 1, 1, bag);
+    player_touch(&mut simulation, TouchKind::End, 1, 1, bag);
+
+    let events = run(&mut simulation, 5.0);
+    assert!(events.contains(&Event::GameOver {
+        reason: GameOverReason::OutOfLives,
+    }));
+    assert_eq!(simulation.players[0].lives, 1);
+    assert_eq!(simulation.winner(), Some(0));
+}
+
+#[test]
+fn versus_players_score_with_their_own_items_only() {
+    let mut config = Config::clone(&config());
+    config.combo.pair_time = 0.0;
+    let mut simulation = simulation_with_config(
+        config,
+        Difficulty {
+            versus: true,
+            ..diff()
+        },
+    );
+    simulation.update(delta_time(&simulation));
+    // The second player holds an item for the whole test
+    let bag = simulation.players[1].bag_position.center();
+    let events = player_touch(&mut simulation, TouchKind::Start, 1, 1, bag);
+    assert_eq!(events, [Event::Picked]);
+    assert_eq!(simulation.multiplier(Some(0)), 1);
+    assert_eq!(simulation.multiplier(Some(1)), 2);
+
+    let per_step = delta_time(&simulation) * simulation.config.juggling_score_multiplier;
+    let scores = |simulation: &Simulation| {
+        simulation
+            .players
+            .iter()
+            .map(|player| player.score)
+            .collect::<Vec<_>>()
+    };
+    let before = scores(&simulation);
+    simulation.update(delta_time(&simulation));
+    let after = scores(&simulation);
+    assert!((after[0] - before[0] - per_step).abs() < 1e-3);
+    assert!((after[1] - before[1] - per_step * 2.0).abs() < 1e-3);
+
+    let mailbox_id = simulation.mailboxes[0].id;
+    throw_item_as(&mut simulation, mailbox_id, 0);
+    let throw_time = simulation.config.throw_time;
+    let delivered = run(&mut simulation, throw_time + 0.1)
+        .into_iter()
+        .find(|event| matches!(event, Event::Delivered { .. }))
+        .expect("item should be delivered");
+    assert_eq!(
+        delivered_score(&delivered),
+        simulation.config.items[0].score
+    );
+}
+
+#[test]
+fn versus_players_build_their_own_delivery_streaks() {
+    let mut config = Config::clone(&config());
+    config.combo.pair_time = 0.0;
+    let mut simulation = simulation_with_config(
+        config,
+        Difficulty {
+            versus: true,
+            ..diff()
+        },
+    );
+    simulation.update(delta_time(&simulation));
+    let deliver_as = |simulation: &mut Simulation, player: usize| {
+        let mailbox_id = simulation.mailboxes[0].id;
+        throw_item_as(simulation, mailbox_id, player);
+        let throw_time = simulation.config.throw_time;
+        run(simulation, throw_time + 0.1)
+            .into_iter()
+            .find(|event| matches!(event, Event::Delivered { .. }))
+            .expect("item should be delivered")
+    };
+    deliver_as(&mut simulation, 0);
+    let event = deliver_as(&mut simulation, 0);
+    assert!(matches!(
+        event,
+        Event::Delivered {
+            streak: 2,
+            player: 0,
+            ..
+        }
+    ));
+    assert_eq!(simulation.players[0].delivery_streak, 2);
+    assert_eq!(simulation.players[1].delivery_streak, 0);
+
+    // The streak of the first player does not multiply the score of the second
+    let event = deliver_as(&mut simulation, 1);
+    assert!(matches!(
+        event,
+        Event::Delivered {
+            streak: 1,
+            player: 1,
+            ..
+        }
+    ));
+    assert_eq!(delivered_score(&event), simulation.config.items[0].score);
+    assert_eq!(simulation.players[0].delivery_streak, 2);
+}
+
+#[test]
+fn mailbox_aim_hovers_the_mailbox() {
+    let mut simulation = simulation(diff());
+    run(&mut simulation, 10.0);
+    let aimed = simulation
+        .mailboxes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, mailbox)| Some((index, simulation.mailbox_aim(mailbox)?)))
+        .collect::<Vec<_>>();
+    assert!(!aimed.is_empty());
+    for (index, aim) in aimed {
+        if simulation.hovered_mailbox(aim).is_some() {
+            assert_eq!(simulation.hovered_mailbox(aim), Some(index));
+            assert!(simulation.perfect_aim(aim, &simulation.mailboxes[index]));
+        }
+    }
+}
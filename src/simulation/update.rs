@@ -0,0 +1,444 @@
+use super::*;
+
+impl Simulation {
+    /// Advance the simulation by a single step
+    pub fn update(&mut self, delta_time: f32) {
+        self.save_prev_state();
+
+        let time_up = self.diff.timed() && self.time_left < 0.0;
+        let out_of_lives = self.out_of_lives();
+        if !self.game_over && (time_up || out_of_lives) {
+            self.game_over = true;
+            while let Some(touch) = self.touches.pop() {
+                self.touch_end(touch.id, touch.position);
+            }
+            self.events.push(Event::GameOver {
+                reason: if out_of_lives {
+                    GameOverReason::OutOfLives
+                } else {
+                    GameOverReason::TimeUp
+                },
+            });
+        }
+
+        let juggling_score = delta_time * self.config.juggling_score_multiplier;
+        if self.diff.versus {
+            // Everyone scores for their own juggling
+            for player in 0..self.players.len() {
+                self.add_raw_score(juggling_score, Some(player));
+            }
+        } else {
+            self.add_raw_score(juggling_score, None);
+        }
+        self.time_left -= delta_time;
+        self.time += delta_time;
+        for effect in &mut self.effects {
+            effect.time_left -= delta_time;
+        }
+        self.effects.retain(|effect| effect.time_left > 0.0);
+        self.update_streaks(delta_time);
+
+        for touch in &mut self.touches {
+            if let Some(remove_time) = &mut touch.remove_time {
+                *remove_time =
+                    (*remove_time + delta_time / self.config.throw_animation_time).min(1.0);
+            }
+            touch.throw_animation_time = (touch.throw_animation_time
+                + delta_time / self.config.throw_animation_time)
+                .min(1.0);
+            if touch.holding.is_some() {
+                touch.throw_animation_time = 0.0;
+            }
+            touch.error_animation_time = (touch.error_animation_time
+                + delta_time / self.config.error_animation_time)
+                .min(1.0);
+            if touch.holding.is_some() {
+                touch.error_animation_time = 1.0;
+            }
+        }
+        self.touches.retain(|touch| {
+            touch.id.is_none()
+                || match touch.remove_time {
+                    Some(time) => time < 1.0,
+                    None => true,
+                }
+        });
+
+        let mut delta_time = delta_time * self.diff.time_scale;
+        if self.effect_active(PowerUpKind::SlowMotion) {
+            delta_time *= self.config.slow_motion_time_scale;
+        }
+
+        let knock = self.update_hazards(delta_time);
+        self.update_juggling_items(delta_time, knock);
+        self.latitude += self.ride_speed() * delta_time; // Move forward
+        self.camera.latitude = self.latitude;
+        self.update_mailboxes();
+        self.update_power_ups();
+        self.update_houses();
+        self.update_thrown_items(delta_time);
+
+        self.tick += 1;
+    }
+
+    fn save_prev_state(&mut self) {
+        self.prev_latitude = self.latitude;
+        for item in &mut self.juggling_items {
+            item.prev_pos = item.pos;
+            item.prev_rot = item.rot;
+        }
+        for item in &mut self.thrown_items {
+            item.prev_t = item.t;
+            item.item.prev_rot = item.item.rot;
+        }
+    }
+
+    /// Returns how much was actually added to the score.
+    /// Score not made by a specific player is shared between everyone
+    fn add_raw_score(&mut self, raw_score: f32, player: Option<usize>) -> f32 {
+        if self.game_over {
+            return 0.0;
+        }
+        let scored = raw_score * self.multiplier(player) as f32;
+        self.score += scored;
+        match player {
+            Some(player) => self.players[player].score += scored,
+            None => {
+                let share = scored / self.players.len() as f32;
+                for player in &mut self.players {
+                    player.score += share;
+                }
+            }
+        }
+        scored
+    }
+
+    /// `knock` is the max sideways speed given to every item
+    fn update_juggling_items(&mut self, delta_time: f32, knock: f32) {
+        let gravity = self.gravity();
+        for item in &mut self.juggling_items {
+            if knock != 0.0 {
+                item.vel.x += self.rng.gen_range(-1.0..1.0) * knock;
+            }
+            item.vel.y -= gravity * self.config.items[item.kind].gravity_scale * delta_time;
+            item.pos += item.vel * delta_time;
+            item.rot += item.w * delta_time;
+        }
+        let mut dropped = Vec::new();
+        self.juggling_items.retain(|item| {
+            if item.pos.y > self.players[item.player].bag_position.min.y {
+                true
+            } else {
+                dropped.push((item.pos, item.player));
+                false
+            }
+        });
+        for (pos, player) in dropped {
+            self.stats.dropped();
+            self.players[player].stats.dropped();
+            self.players[player].delivery_streak = 0;
+            self.events.push(Event::Dropped { pos });
+            self.lose_life(player);
+        }
+    }
+
+    fn update_streaks(&mut self, delta_time: f32) {
+        for player in &mut self.players {
+            if player.delivery_streak == 0 {
+                continue;
+            }
+            player.streak_decay -= delta_time;
+            if player.streak_decay < 0.0 {
+                player.delivery_streak -= 1;
+                player.streak_decay += self.config.combo.decay_time;
+            }
+        }
+    }
+
+    fn update_houses(&mut self) {
+        self.houses.retain(|house| {
+            house.latitude > self.latitude - self.config.despawn_distance.to_radians()
+        });
+        while self.houses.last().map_or(true, |house| {
+            house.latitude < self.latitude + self.config.spawn_distance.to_radians()
+        }) {
+            let last_latitude = self
+                .houses
+                .last()
+                .map_or(self.latitude, |house| house.latitude);
+            for x in [-1, 1] {
+                self.houses.push(House {
+                    x: x as f32 * (self.config.road_width + self.config.house_offset),
+                    latitude: last_latitude + self.config.distance_between_houses.to_radians(),
+                    texture: self.road_rng.gen_range(0..self.asset_info.house_textures),
+                });
+            }
+        }
+    }
+
+    fn update_mailboxes(&mut self) {
+        self.mailboxes.retain(|mailbox| {
+            mailbox.latitude > self.latitude - self.config.despawn_distance.to_radians()
+        });
+        while self.mailboxes.last().map_or(true, |mailbox| {
+            mailbox.latitude < self.latitude + self.config.spawn_distance.to_radians()
+        }) {
+            let last_latitude = self
+                .mailboxes
+                .last()
+                .map_or(self.latitude, |mailbox| mailbox.latitude);
+            let latitude = last_latitude + self.distance_between_mailboxes().to_radians();
+            let (left, right) = if self.road_rng.gen_bool(self.double_mailbox_probability()) {
+                (true, true)
+            } else if self.road_rng.gen() {
+                (true, false)
+            } else {
+                (false, true)
+            };
+            for (x, spawn) in itertools::izip![[-1, 1], [left, right]] {
+                if !spawn {
+                    continue;
+                }
+                self.mailboxes.push(Mailbox {
+                    id: self.next_id,
+                    x: x as f32 * (self.config.road_width + self.config.mailbox_size / 2.0),
+                    latitude,
+                    color: self.road_rng.gen_range(0..self.config.mailbox_colors.len()),
+                });
+                self.next_id += 1;
+            }
+        }
+    }
+
+    /// Spawned and despawned the same way as mailboxes
+    fn update_power_ups(&mut self) {
+        if self.config.power_ups.is_empty() {
+            return;
+        }
+        self.power_ups.retain(|power_up| {
+            power_up.latitude > self.latitude - self.config.despawn_distance.to_radians()
+        });
+        while self.power_ups.last().map_or(true, |power_up| {
+            power_up.latitude < self.latitude + self.config.spawn_distance.to_radians()
+        }) {
+            let last_latitude = self
+                .power_ups
+                .last()
+                .map_or(self.latitude, |power_up| power_up.latitude);
+            let weights = self.config.power_ups.iter().map(|power_up| power_up.weight);
+            let chosen = &self.config.power_ups[self
+                .road_rng
+                .sample(rand::distributions::WeightedIndex::new(weights).unwrap())];
+            self.power_ups.push(PowerUp {
+                id: self.next_id,
+                x: self.road_rng.gen_range(-1.0..1.0) * self.config.road_width / 2.0,
+                latitude: last_latitude + self.config.distance_between_power_ups.to_radians(),
+                kind: chosen.kind,
+                duration: chosen.duration,
+            });
+            self.next_id += 1;
+        }
+    }
+
+    /// Returns how much juggled items get knocked by the hazards ridden over
+    fn update_hazards(&mut self, delta_time: f32) -> f32 {
+        for hazard in &mut self.hazards {
+            hazard.latitude += self.config.hazards[hazard.kind].speed * delta_time;
+        }
+        self.hazards.retain(|hazard| {
+            hazard.latitude > self.latitude - self.config.despawn_distance.to_radians()
+        });
+
+        let mut knock = 0.0;
+        for hazard in &mut self.hazards {
+            if !hazard.passed && hazard.latitude < self.latitude {
+                hazard.passed = true;
+                let kind = &self.config.hazards[hazard.kind];
+                // Hazards on the side of the road are ridden past
+                if hazard.x.abs() < (kind.size + self.config.bike_width) / 2.0 {
+                    knock += kind.knock;
+                    self.events.push(Event::HazardHit { kind: hazard.kind });
+                }
+            }
+        }
+
+        while self.next_hazard_latitude < self.latitude + self.config.spawn_distance.to_radians() {
+            for (index, kind) in self.config.hazards.iter().enumerate() {
+                let chance = kind.spawn_chance(self.time, self.config.hazard_ramp_time);
+                if !self.road_rng.gen_bool(chance.clamp(0.0, 1.0) as f64) {
+                    continue;
+                }
+                let side = if self.road_rng.gen() { 1.0 } else { -1.0 };
+                let [min_offset, max_offset] = kind.offset;
+                self.hazards.push(Hazard {
+                    kind: index,
+                    x: side * self.road_rng.gen_range(min_offset..=max_offset),
+                    latitude: self.next_hazard_latitude,
+                    passed: false,
+                });
+            }
+            self.next_hazard_latitude += self.config.distance_between_hazards.to_radians();
+        }
+        knock
+    }
+
+    /// `player` is who hit the power-up
+    fn apply_power_up(&mut self, power_up: &PowerUp, player: usize) {
+        match power_up.kind {
+            PowerUpKind::ExtraLife if self.diff.versus => {
+                let lives = &mut self.players[player].lives;
+                *lives = (*lives + 1).min(self.diff.lives);
+            }
+            PowerUpKind::ExtraLife => self.lives = (self.lives + 1).min(self.diff.lives),
+            kind => {
+                // Hitting the same kind again restarts the effect
+                self.effects.retain(|effect| effect.kind != kind);
+                self.effects.push(Effect {
+                    kind,
+                    time_left: power_up.duration,
+                });
+            }
+        }
+    }
+
+    fn update_thrown_items(&mut self, delta_time: f32) {
+        for item in &mut self.thrown_items {
+            item.t += delta_time;
+            item.item.rot += item.item.w * delta_time;
+        }
+        let mut delivered = Vec::new();
+        let mut missed = Vec::new();
+        let mut rejected = Vec::new();
+        let mut power_ups_hit = Vec::new();
+        self.thrown_items.retain(|item| {
+            if item.t < self.config.throw_time {
+                true
+            } else {
+                let mailbox = self
+                    .mailboxes
+                    .iter()
+                    .position(|mailbox| mailbox.id == item.to_id);
+                let power_up = self
+                    .power_ups
+                    .iter()
+                    .position(|power_up| power_up.id == item.to_id);
+                let kind = &self.config.items[item.kind];
+                match (mailbox, power_up) {
+                    (Some(index), _)
+                        if self.config.accepts(item.kind, self.mailboxes[index].color) =>
+                    {
+                        let mailbox = self.mailboxes.remove(index);
+                        let color_match = item.mailbox_color.map(|color| color == mailbox.color);
+                        delivered.push((
+                            item.to,
+                            kind.score,
+                            color_match,
+                            mailbox.latitude,
+                            item.perfect,
+                            item.player,
+                        ));
+                    }
+                    // Mailbox stays for another item to be delivered
+                    (Some(_), _) => rejected.push((item.to, item.player)),
+                    (None, Some(index)) => {
+                        power_ups_hit.push((item.to, self.power_ups.remove(index), item.player));
+                    }
+                    // Also when another item got there first and claimed the mailbox
+                    (None, None) => missed.push((item.to, item.player)),
+                }
+                false
+            }
+        });
+        for (pos, power_up, player) in power_ups_hit {
+            self.events.push(Event::PowerUpHit {
+                pos,
+                kind: power_up.kind,
+            });
+            self.apply_power_up(&power_up, player);
+        }
+        self.stats.deliveries += delivered.len();
+        self.stats.misses += missed.len() + rejected.len();
+        for &(.., player) in &delivered {
+            self.players[player].stats.deliveries += 1;
+        }
+        for &(_, player) in missed.iter().chain(&rejected) {
+            self.players[player].stats.misses += 1;
+            self.players[player].delivery_streak = 0;
+        }
+        for (pos, score, color_match, latitude, perfect, player) in delivered {
+            let combo = &self.config.combo;
+            let time = self.time;
+            let thrower = &mut self.players[player];
+            thrower.delivery_streak += 1;
+            thrower.streak_decay = combo.decay_time;
+            // Both mailboxes of a pair are at the same latitude
+            let pair = thrower
+                .last_delivery
+                .is_some_and(|(last_latitude, last_time)| {
+                    last_latitude == latitude && time - last_time < combo.pair_time
+                });
+            thrower.last_delivery = Some((latitude, time));
+            let streak = thrower.delivery_streak;
+            let mut raw_score = score;
+            if pair {
+                raw_score += combo.pair_bonus;
+            }
+            if perfect {
+                raw_score += combo.perfect_bonus;
+            }
+            let mut lose_life = false;
+            match color_match {
+                Some(true) => raw_score += self.config.color_match_bonus,
+                Some(false) => match self.config.color_mismatch_penalty {
+                    ColorMismatchPenalty::Bonus => raw_score -= self.config.color_match_bonus,
+                    ColorMismatchPenalty::Life => lose_life = true,
+                },
+                None => {}
+            }
+            let streak_multiplier = combo.streak_multiplier(streak);
+            let score = self.add_raw_score(raw_score * streak_multiplier as f32, Some(player));
+            self.events.push(Event::Delivered {
+                pos,
+                score,
+                color_match,
+                streak,
+                pair,
+                perfect,
+                player,
+            });
+            if lose_life {
+                self.lose_life(player);
+            }
+        }
+        for (pos, player) in missed {
+            self.events.push(Event::Missed { pos });
+            self.lose_life(player);
+        }
+        for (pos, player) in rejected {
+            self.events.push(Event::Rejected { pos });
+            self.lose_life(player);
+        }
+    }
+
+    /// `player` is who made the mistake, only matters in versus mode
+    fn lose_life(&mut self, player: usize) {
+        if self.diff.practice {
+            self.events.push(Event::LifeSpared);
+            return;
+        }
+        if self.diff.versus {
+            let lives = &mut self.players[player].lives;
+            if *lives != 0 {
+                *lives -= 1;
+                self.events.push(Event::LifeLost { lives_left: *lives });
+            }
+            return;
+        }
+        if self.lives != 0 {
+            self.lives -= 1;
+            self.events.push(Event::LifeLost {
+                lives_left: self.lives,
+            });
+        }
+    }
+}
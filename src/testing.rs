@@ -0,0 +1,31 @@
+//! Fixtures shared by the tests of every module
+
+use super::*;
+
+use crate::simulation::{AssetInfo, Simulation};
+
+pub(crate) fn config() -> Rc<Config> {
+    Rc::new(toml::from_str(include_str!("../assets/config.toml")).unwrap())
+}
+
+/// A minute long run with three lives, tests of a mode turn it on with `..diff()`
+pub(crate) fn diff() -> Difficulty {
+    Difficulty {
+        time_scale: 1.0,
+        game_time: 60.0,
+        lives: 3,
+        ..default()
+    }
+}
+
+pub(crate) fn simulation(diff: Difficulty) -> Simulation {
+    simulation_with_config(Config::clone(&config()), diff)
+}
+
+pub(crate) fn simulation_with_config(config: Config, diff: Difficulty) -> Simulation {
+    let asset_info = AssetInfo {
+        item_aspects: vec![1.0; config.items.len()],
+        house_textures: 3,
+    };
+    Simulation::new(&Rc::new(config), diff, 42, asset_info)
+}
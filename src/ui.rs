@@ -200,6 +200,74 @@ impl geng::ui::Widget for Text<'_> {
     }
 }
 
+pub struct TextButton<'a> {
+    time: &'a mut f64,
+    sense: &'a mut geng::ui::Sense,
+    clicked: bool,
+    font: &'a Font,
+    text: String,
+}
+
+impl<'a> TextButton<'a> {
+    pub fn new(cx: &'a geng::ui::Controller, font: &'a Font, text: String, sfx: &'a UiSfx) -> Self {
+        let sense: &'a mut geng::ui::Sense = cx.get_state();
+        let clicked = sense.take_clicked();
+        if clicked {
+            sfx.click.play();
+        }
+        let last_hover: &'a mut bool = cx.get_state();
+        if *last_hover != sense.is_hovered() {
+            *last_hover = sense.is_hovered();
+            sfx.hover.play();
+        }
+        Self {
+            time: cx.get_state(),
+            clicked,
+            sense,
+            font,
+            text,
+        }
+    }
+    pub fn was_clicked(&self) -> bool {
+        self.clicked
+    }
+}
+
+impl geng::ui::Widget for TextButton<'_> {
+    fn update(&mut self, delta_time: f64) {
+        *self.time += delta_time;
+    }
+    fn sense(&mut self) -> Option<&mut geng::ui::Sense> {
+        Some(self.sense)
+    }
+    fn calc_constraints(
+        &mut self,
+        _children: &geng::ui::ConstraintsContext,
+    ) -> geng::ui::Constraints {
+        default()
+    }
+    fn draw(&mut self, cx: &mut geng::ui::DrawContext) {
+        let extra = 0.1;
+        let size = if self.sense.is_captured() {
+            1.0 - extra
+        } else if self.sense.is_hovered() {
+            1.0 + extra
+        } else {
+            1.0
+        };
+        self.font.draw(
+            cx.framebuffer,
+            &geng::PixelPerfectCamera,
+            &self.text,
+            "#858585".try_into().unwrap(),
+            mat3::translate(vec2(cx.position.center().x, cx.position.min.y).map(|x| x as f32))
+                * mat3::scale_uniform(cx.position.height() as f32 * size)
+                * mat3::rotate(Angle::from_radians(self.time.sin() as f32 * 0.05))
+                * mat3::translate(-vec2(self.text.len() as f32 / 2.0, 0.0)),
+        );
+    }
+}
+
 pub struct TextInput<'a> {
     cursor_anim_time: &'a mut f64,
     sense: &'a mut geng::ui::Sense,
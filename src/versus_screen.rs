@@ -0,0 +1,141 @@
+use super::*;
+
+use crate::simulation::Stats;
+
+/// What a player did in a versus run
+#[derive(Debug, Clone)]
+pub struct VersusPlayer {
+    pub profile: Profile,
+    pub score: f32,
+    pub lives: usize,
+    pub stats: Stats,
+}
+
+/// Shows who won a versus run and how every player did, versus runs are not submitted anywhere
+pub struct VersusScreen {
+    geng: Geng,
+    assets: Rc<Assets>,
+    config: Rc<Config>,
+    options: Rc<Options>,
+    diff: Difficulty,
+    players: Vec<VersusPlayer>,
+    /// Index into [VersusScreen::players], [None] on a draw
+    winner: Option<usize>,
+
+    transition: Option<geng::state::Transition>,
+}
+
+impl VersusScreen {
+    pub fn new(
+        geng: &Geng,
+        assets: &Rc<Assets>,
+        config: &Rc<Config>,
+        options: &Rc<Options>,
+        diff: Difficulty,
+        players: Vec<VersusPlayer>,
+        winner: Option<usize>,
+    ) -> Self {
+        Self {
+            geng: geng.clone(),
+            assets: assets.clone(),
+            config: config.clone(),
+            options: options.clone(),
+            diff,
+            players,
+            winner,
+            transition: None,
+        }
+    }
+}
+
+impl geng::State for VersusScreen {
+    fn transition(&mut self) -> Option<geng::state::Transition> {
+        self.transition.take()
+    }
+    fn draw(&mut self, framebuffer: &mut ugli::Framebuffer) {
+        self.geng
+            .window()
+            .set_cursor_type(geng::CursorType::Default);
+        ugli::clear(framebuffer, Some(Rgba::BLACK), None, None);
+    }
+
+    fn ui<'a>(&'a mut self, cx: &'a geng::ui::Controller) -> Box<dyn geng::ui::Widget + 'a> {
+        use geng::ui::*;
+        let menu = ui::TextureButton::new(cx, &self.assets.menu, &self.assets.ui_sfx);
+        if menu.was_clicked() {
+            self.transition = Some(geng::state::Transition::Pop);
+        }
+        let play = ui::TextureButton::new(cx, &self.assets.play_again, &self.assets.ui_sfx);
+        if play.was_clicked() {
+            let (diff, seed) = daily::play_again(&self.config, &self.options, &self.diff);
+            self.transition = Some(geng::state::Transition::Switch(Box::new(Game::new(
+                &self.geng,
+                &self.assets,
+                &self.config,
+                &self.options,
+                diff,
+                self.players
+                    .iter()
+                    .map(|player| player.profile.clone())
+                    .collect(),
+                seed,
+                RunMode::played(),
+            ))));
+        }
+        let title = ui::Text::new(
+            &self.assets.font,
+            match self.winner {
+                Some(index) => format!("{} wins", self.players[index].profile.name),
+                None => "draw".to_owned(),
+            },
+        );
+
+        let mut stack = stack![
+            ui::TextureWidget::new(&self.assets.screen_background),
+            title.fixed_size(vec2(0.0, 16.0)).place(200, 85),
+            menu.place(25, 235),
+            play.place(180, 220),
+        ];
+        let labels = [
+            "",
+            "score",
+            "delivered",
+            "dropped",
+            "accuracy",
+            "streak",
+            "lives",
+        ];
+        for (row, label) in labels.into_iter().enumerate() {
+            let label = ui::Text::left_align(&self.assets.font, label.to_owned());
+            stack.push(Box::new(
+                label
+                    .fixed_size(vec2(0.0, 10.0))
+                    .place(25, 110 + row as i32 * 13),
+            ));
+        }
+        for (column, player) in self.players.iter().enumerate() {
+            let accuracy = match player.stats.accuracy() {
+                Some(accuracy) => ((accuracy * 100.0).round() as i32).to_string(),
+                None => "-".to_owned(),
+            };
+            let values = [
+                player.profile.name.clone(),
+                (player.score.floor() as i32).to_string(),
+                player.stats.deliveries.to_string(),
+                player.stats.drops.to_string(),
+                accuracy,
+                player.stats.longest_juggle_streak.to_string(),
+                player.lives.to_string(),
+            ];
+            for (row, value) in values.into_iter().enumerate() {
+                let value = ui::Text::right_align(&self.assets.font, value);
+                stack.push(Box::new(
+                    value
+                        .fixed_size(vec2(0.0, 10.0))
+                        .place(280 + column as i32 * 95, 110 + row as i32 * 13),
+                ));
+            }
+        }
+        stack.center().boxed()
+    }
+}